@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+// Arbitrary-precision signed integer, the magnitude is stored least significant limb first,
+// without trailing zero limbs, so that zero has no limb and is never negative
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    fn div_rem(&self, divisor: u32) -> (Self, u32) {
+        let mut remainder = 0u64;
+        let mut limbs = vec![0; self.limbs.len()];
+        for (index, limb) in self.limbs.iter().enumerate().rev() {
+            let current = remainder << 32 | *limb as u64;
+            limbs[index] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        (BigInt::from_parts(self.negative, limbs), remainder as u32)
+    }
+
+    // Only meant for exact divisions, the remainder is dropped
+    pub fn div_exact(&self, divisor: u32) -> Self {
+        self.div_rem(divisor).0
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for index in 0..a.len().max(b.len()) {
+        let sum = *a.get(index).unwrap_or(&0) as u64 + *b.get(index).unwrap_or(&0) as u64 + carry;
+        limbs.push(sum as u32);
+        carry = sum >> 32;
+    }
+    limbs.push(carry as u32);
+    limbs
+}
+
+// The first magnitude must be the largest one
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (index, limb) in a.iter().enumerate() {
+        let mut difference = *limb as i64 - *b.get(index).unwrap_or(&0) as i64 - borrow;
+        borrow = (difference < 0) as i64;
+        difference += borrow << 32;
+        limbs.push(difference as u32);
+    }
+    limbs
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(
+            value < 0,
+            (0..4)
+                .map(|index| (magnitude >> (32 * index)) as u32)
+                .collect(),
+        )
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitudes(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let product = *a as u64 * *b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, limbs)
+    }
+}
+
+// Peel off 9 decimal digits at a time
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+        let mut magnitude = BigInt::from_parts(false, self.limbs.clone());
+        let mut chunks = vec![];
+        while !magnitude.limbs.is_empty() {
+            let (quotient, chunk) = magnitude.div_rem(1_000_000_000);
+            chunks.push(chunk);
+            magnitude = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use std::env;
+
+pub fn flag(name: &str) -> bool {
+    env::args().skip(1).any(|arg| arg == name)
+}
+
+pub fn value(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
@@ -1,9 +1,13 @@
+use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
 use std::path::Path;
 
 use itertools::Itertools;
 
+use crate::cli;
+use crate::polygon::Polygon;
+
 #[derive(Debug, PartialEq, Eq)]
 enum Tile {
     Start,
@@ -16,7 +20,7 @@ enum Tile {
     SWBend,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Direction {
     North,
     South,
@@ -150,8 +154,73 @@ pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
     Ok(())
 }
 
+fn direction_to(from: (usize, usize), to: (usize, usize)) -> Direction {
+    if to.0 < from.0 {
+        North
+    } else if to.0 > from.0 {
+        South
+    } else if to.1 < from.1 {
+        West
+    } else {
+        East
+    }
+}
+
+// The start pipe connects the first and last tiles of the loop
+fn start_tile(loop_path: &[(usize, usize)]) -> Tile {
+    let start = loop_path[0];
+    let first = direction_to(start, loop_path[1]);
+    let last = direction_to(start, *loop_path.last().unwrap());
+    match (first, last) {
+        (North, South) | (South, North) => Vertical,
+        (East, West) | (West, East) => Horizontal,
+        (North, East) | (East, North) => NEBend,
+        (North, West) | (West, North) => NWBend,
+        (South, East) | (East, South) => SEBend,
+        (South, West) | (West, South) => SWBend,
+        _ => unreachable!(),
+    }
+}
+
+// Box-drawing rendering of the map: the loop in bold, every other tile marked I (inside) or O (outside), junk pipes dimmed
+fn display_loop(map: &[Vec<Tile>], loop_path: &[(usize, usize)]) -> String {
+    let in_loop: HashSet<&(usize, usize)> = loop_path.iter().collect();
+    let start = start_tile(loop_path);
+    let mut formatted = String::new();
+
+    for (line_no, line) in map.iter().enumerate() {
+        // Crossing a loop pipe going north flips between outside and inside
+        let mut inside = false;
+        for (col_no, tile) in line.iter().enumerate() {
+            let tile = if *tile == Start { &start } else { tile };
+            let side = if inside { 'I' } else { 'O' };
+            if in_loop.contains(&(line_no, col_no)) {
+                if matches!(tile, Vertical | NEBend | NWBend) {
+                    inside = !inside;
+                }
+                let pipe = match tile {
+                    Vertical => '│',
+                    Horizontal => '─',
+                    NEBend => '└',
+                    NWBend => '┘',
+                    SWBend => '┐',
+                    SEBend => '┌',
+                    Ground | Start => unreachable!(),
+                };
+                formatted += &format!("\x1b[1m{}\x1b[0m", pipe);
+            } else if *tile == Ground {
+                formatted.push(side);
+            } else {
+                formatted += &format!("\x1b[2m{}\x1b[0m", side);
+            }
+        }
+        formatted += "\n";
+    }
+    formatted
+}
+
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let (mut map, start) = get_input();
+    let (map, start) = get_input();
 
     let mut ghosts = vec![
         (start, North, vec![]),
@@ -244,97 +313,19 @@ pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
         }
     }
 
-    let (first_tile, last_tile) = (loop_path[1], *loop_path.last().unwrap());
-    let start_tile = if first_tile.0 == last_tile.0 {
-        Horizontal
-    } else if first_tile.1 == last_tile.1 {
-        Vertical
-    } else if start.0 == first_tile.0 {
-        if first_tile.0 < last_tile.0 && first_tile.1 < last_tile.1 {
-            SWBend
-        } else if first_tile.0 < last_tile.0 && first_tile.1 > last_tile.1 {
-            SEBend
-        } else if first_tile.0 > last_tile.0 && first_tile.1 < last_tile.1 {
-            NWBend
-        } else {
-            NEBend
-        }
-    } else if first_tile.0 < last_tile.0 && first_tile.1 < last_tile.1 {
-        NEBend
-    } else if first_tile.0 < last_tile.0 && first_tile.1 > last_tile.1 {
-        NWBend
-    } else if first_tile.0 > last_tile.0 && first_tile.1 < last_tile.1 {
-        SEBend
-    } else {
-        SWBend
-    };
-
-    map[start.0][start.1] = start_tile;
+    // The loop only moves one tile at a time, every tile of it is a boundary point of the polygon
+    let pipe_loop = Polygon::from_vertices(
+        loop_path
+            .iter()
+            .map(|&(line_no, col_no)| (line_no as i64, col_no as i64))
+            .collect(),
+    );
+    let count_internal = pipe_loop.interior_points();
 
-    let mut insiders = vec![];
+    if cli::flag("--render") {
+        print!("{}", display_loop(&map, &loop_path));
+    }
 
-    // Accidentally saw this solution on the reddit after solving day 11
-    // I didn't read the explanation, but if I suppose that because it's a loop, I pass over the loop an even number of time
-    let count_internal = map
-        .iter()
-        .enumerate()
-        .map(|(line_no, line)| {
-            let mut north = false;
-            let mut south = false;
-            let mut inside = false;
-            line.iter()
-                .enumerate()
-                .filter(|&(col_no, tile)| {
-                    let in_loop = loop_path.contains(&(line_no, col_no));
-                    let is_inside = match tile {
-                        Ground => inside,
-                        Horizontal => !in_loop && inside,
-                        Vertical => {
-                            if in_loop {
-                                inside = !inside;
-                                north = false;
-                                south = false;
-                                false
-                            } else {
-                                inside
-                            }
-                        }
-                        NEBend | NWBend => {
-                            if in_loop {
-                                north = !north;
-                                if north && south {
-                                    inside = !inside;
-                                    north = false;
-                                    south = false;
-                                }
-                                false
-                            } else {
-                                inside
-                            }
-                        }
-                        SWBend | SEBend => {
-                            if in_loop {
-                                south = !south;
-                                if north && south {
-                                    inside = !inside;
-                                    north = false;
-                                    south = false;
-                                }
-                                false
-                            } else {
-                                inside
-                            }
-                        }
-                        _ => unreachable!(),
-                    };
-                    if is_inside {
-                        insiders.push((line_no, col_no));
-                    }
-                    is_inside
-                })
-                .count()
-        })
-        .sum::<usize>();
     println!("There are {} elements inside the loop", count_internal);
     Ok(())
 }
@@ -11,6 +11,8 @@ enum Tile {
 use itertools::Itertools;
 use Tile::*;
 
+use crate::cli;
+
 type Field = Vec<Vec<Tile>>;
 
 fn get_input() -> Vec<Field> {
@@ -36,111 +38,134 @@ fn get_input() -> Vec<Field> {
         })
         .collect_vec()
 }
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let fields = get_input();
-    let mut horizontal_mirrors = 0usize;
-    let mut vertical_mirrors = 0usize;
-
-    'field: for field in fields {
-        let width = field[0].len();
-        let height = field.len();
-        // horizontal flip detection
-        for i in 1..height {
-            let up = field.iter().take(i).rev();
-            let down = field.iter().skip(i);
-            if up.zip(down).all(|(up_line, down_line)| {
-                up_line
-                    .iter()
-                    .zip(down_line.iter())
-                    .all(|(up_tile, down_tile)| up_tile == down_tile)
-            }) {
-                horizontal_mirrors += i;
-                continue 'field;
-            }
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+// The mirror lies between line (or column) index - 1 and index
+#[derive(Debug, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    index: usize,
+}
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.index,
+            Axis::Vertical => self.index,
         }
-        // vertical flip detection
-        for j in 1..width {
-            if field.iter().all(|line| {
-                let left = line.iter().take(j).rev();
-                let right = line.iter().skip(j);
-                left.zip(right)
-                    .all(|(left_tile, right_tile)| left_tile == right_tile)
-            }) {
-                vertical_mirrors += j;
-                continue 'field;
+    }
+}
+
+// Lines and columns as bitmasks of their rocks, which limits fields to 64 tiles on each side
+fn get_masks(field: &Field) -> Result<(Vec<u64>, Vec<u64>), Box<dyn Error + 'static>> {
+    let (height, width) = (field.len(), field[0].len());
+    if height > u64::BITS as usize || width > u64::BITS as usize {
+        return Err(format!("Field too large to track: {}x{}", height, width).into());
+    }
+    let lines = field
+        .iter()
+        .map(|line| {
+            line.iter()
+                .fold(0, |mask, tile| mask << 1 | (*tile == Rock) as u64)
+        })
+        .collect_vec();
+    let cols = (0..field[0].len())
+        .map(|col_no| {
+            field
+                .iter()
+                .fold(0, |mask, line| mask << 1 | (line[col_no] == Rock) as u64)
+        })
+        .collect_vec();
+    Ok((lines, cols))
+}
+
+// Mirrors for which exactly `smudges` tiles differ from their reflection
+fn mirrors(masks: &[u64], smudges: u32) -> impl Iterator<Item = usize> + '_ {
+    (1..masks.len()).filter(move |&index| {
+        let mut differences = 0;
+        for (before, after) in masks[..index].iter().rev().zip(masks[index..].iter()) {
+            differences += (before ^ after).count_ones();
+            if differences > smudges {
+                return false;
             }
         }
+        differences == smudges
+    })
+}
+
+fn find_reflections(
+    field: &Field,
+    smudges: u32,
+) -> Result<Vec<Reflection>, Box<dyn Error + 'static>> {
+    let (lines, cols) = get_masks(field)?;
+    Ok(mirrors(&lines, smudges)
+        .map(|index| Reflection {
+            axis: Axis::Horizontal,
+            index,
+        })
+        .chain(mirrors(&cols, smudges).map(|index| Reflection {
+            axis: Axis::Vertical,
+            index,
+        }))
+        .collect())
+}
+
+fn find_reflection(
+    field: &Field,
+    smudges: u32,
+) -> Result<Option<Reflection>, Box<dyn Error + 'static>> {
+    Ok(find_reflections(field, smudges)?.into_iter().next())
+}
+
+fn summarize(fields: &[Field], smudges: u32) -> Result<usize, Box<dyn Error + 'static>> {
+    let mut total = 0;
+    for field in fields {
+        if let Some(reflection) = find_reflection(field, smudges)? {
+            total += reflection.summary();
+        }
     }
+    Ok(total)
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let fields = get_input();
     println!(
         "Value obtained after summarizing all notes is: {}",
-        vertical_mirrors + 100 * horizontal_mirrors
+        summarize(&fields, 0)?
     );
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
     let fields = get_input();
-    let mut horizontal_mirrors = 0usize;
-    let mut vertical_mirrors = 0usize;
-
-    'field: for field in fields {
-        let width = field[0].len();
-        let height = field.len();
-        // horizontal flip detection with exactly 1 error
-        for i in 1..height {
-            let up = field.iter().take(i).rev();
-            let down = field.iter().skip(i);
-            let mut error_counter = 0;
-            for (line_up, line_down) in up.zip(down) {
-                error_counter +=
-                    line_up
-                        .iter()
-                        .zip(line_down.iter())
-                        .fold(
-                            0,
-                            |acc, (up_tile, down_tile)| {
-                                if up_tile != down_tile {
-                                    acc + 1
-                                } else {
-                                    acc
-                                }
-                            },
-                        );
-                if error_counter > 1 {
-                    break;
-                }
-            }
-            if error_counter == 1 {
-                horizontal_mirrors += i;
-                continue 'field;
-            }
-        }
-        // vertical flip detection
-        for j in 1..width {
-            let mut error_counter = 0;
-            for line in field.iter() {
-                let left = line.iter().take(j).rev();
-                let right = line.iter().skip(j);
-                error_counter += left.zip(right).fold(0, |acc, (left_tile, right_tile)| {
-                    if left_tile != right_tile {
-                        acc + 1
-                    } else {
-                        acc
-                    }
-                });
-                if error_counter > 1 {
-                    break;
-                }
-            }
-            if error_counter == 1 {
-                vertical_mirrors += j;
-                continue 'field;
-            }
+    // --smudges <count> looks for mirrors off by that many tiles instead of a single one
+    let smudges = match cli::value("--smudges") {
+        Some(smudges) => smudges.parse::<u32>()?,
+        None => 1,
+    };
+
+    // --all lists every reflection line of each field, not only the first one
+    if cli::flag("--all") {
+        for (field_no, field) in fields.iter().enumerate() {
+            println!(
+                "Field {}: {}",
+                field_no + 1,
+                find_reflections(field, smudges)?
+                    .iter()
+                    .map(|reflection| format!("{:?} {}", reflection.axis, reflection.index))
+                    .join(", ")
+            );
         }
     }
+
     println!(
         "After fixing the smudge, the value obtained after summarizing all notes is: {}",
-        vertical_mirrors + 100 * horizontal_mirrors
+        summarize(&fields, smudges)?
     );
     Ok(())
 }
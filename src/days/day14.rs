@@ -3,56 +3,7 @@ use std::error::Error;
 use std::fs;
 use std::path::Path;
 
-#[derive(Debug, PartialEq)]
-enum Tile {
-    Round,
-    Square,
-    Empty,
-}
-
-use Tile::*;
-
-fn get_input() -> Vec<Vec<Tile>> {
-    let input = fs::read_to_string(Path::new("./input/day14.input"))
-        .expect("Something went wrong with the input");
-    input
-        .trim()
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|tile| match tile {
-                    '.' => Empty,
-                    'O' => Round,
-                    '#' => Square,
-                    _ => unreachable!(),
-                })
-                .collect()
-        })
-        .collect()
-}
-
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let input = get_input();
-    let (height, width) = (input.len(), input[0].len());
-    let mut max_weight = 0;
-
-    for col in 0..width {
-        let mut max_height = 0;
-        for (line_no, line) in input.iter().enumerate() {
-            if line[col] == Round {
-                max_weight += height - max_height;
-                max_height += 1;
-            }
-            if line[col] == Square {
-                max_height = line_no + 1;
-            }
-        }
-    }
-
-    println!("The total load of the north support beam is {}", max_weight);
-
-    Ok(())
-}
+use crate::cli;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
@@ -62,135 +13,160 @@ enum Direction {
     West,
 }
 
-use itertools::Itertools;
 use Direction::*;
 
-fn move_rocks(map: &mut Vec<Vec<Tile>>, direction: Direction) {
-    let (height, width) = (map.len(), map[0].len());
-
-    let (outer_loop, inner_loop, is_swap, is_reverse) = match direction {
-        North => (
-            (0..width).collect_vec(),
-            (0..height).collect_vec(),
-            true,
-            false,
-        ),
-        East => (
-            (0..height).collect_vec(),
-            (0..width).rev().collect_vec(),
-            false,
-            true,
-        ),
-        South => (
-            (0..width).collect_vec(),
-            (0..height).rev().collect_vec(),
-            true,
-            true,
-        ),
-        West => (
-            (0..height).collect_vec(),
-            (0..width).collect_vec(),
-            false,
-            false,
-        ),
-    };
+// Each line is a bitmask, bit i standing for column i, which limits the platform to 128 columns
+#[derive(Debug, Clone)]
+struct Platform {
+    width: usize,
+    round: Vec<u128>,
+    square: Vec<u128>,
+}
 
-    for i in outer_loop {
-        let mut fall_value = *inner_loop.first().unwrap();
-        for &j in &inner_loop {
-            if is_swap {
-                if map[j][i] == Round {
-                    map[j][i] = Empty;
-                    map[fall_value][i] = Round;
-                    fall_value = if is_reverse {
-                        fall_value.saturating_sub(1)
-                    } else {
-                        fall_value + 1
-                    };
-                }
+impl Platform {
+    fn free(&self, line_no: usize) -> u128 {
+        !(self.round[line_no] | self.square[line_no]) & (u128::MAX >> (128 - self.width))
+    }
 
-                if map[j][i] == Square {
-                    fall_value = if is_reverse {
-                        j.saturating_sub(1)
-                    } else {
-                        j + 1
-                    };
+    // Move every rock one step at a time until none of them can move anymore
+    fn tilt(&mut self, direction: Direction) {
+        let height = self.round.len();
+        let mut moved = true;
+        while moved {
+            moved = false;
+            match direction {
+                North | South => {
+                    for step in 1..height {
+                        let (from, to) = if direction == North {
+                            (step, step - 1)
+                        } else {
+                            (height - 1 - step, height - step)
+                        };
+                        let movable = self.round[from] & self.free(to);
+                        if movable != 0 {
+                            self.round[from] &= !movable;
+                            self.round[to] |= movable;
+                            moved = true;
+                        }
+                    }
                 }
-            } else {
-                if map[i][j] == Round {
-                    map[i][j] = Empty;
-                    map[i][fall_value] = Round;
-                    fall_value = if is_reverse {
-                        fall_value.saturating_sub(1)
-                    } else {
-                        fall_value + 1
-                    };
+                West | East => {
+                    for line_no in 0..height {
+                        let free = self.free(line_no);
+                        let line = &mut self.round[line_no];
+                        let movable = if direction == West {
+                            *line & free << 1
+                        } else {
+                            *line & free >> 1
+                        };
+                        if movable != 0 {
+                            *line &= !movable;
+                            *line |= if direction == West {
+                                movable >> 1
+                            } else {
+                                movable << 1
+                            };
+                            moved = true;
+                        }
+                    }
                 }
+            }
+        }
+    }
+
+    fn load(&self) -> u64 {
+        let height = self.round.len();
+        self.round
+            .iter()
+            .enumerate()
+            .map(|(line_no, line)| (height - line_no) as u64 * line.count_ones() as u64)
+            .sum()
+    }
 
-                if map[i][j] == Square {
-                    fall_value = if is_reverse {
-                        j.saturating_sub(1)
-                    } else {
-                        j + 1
-                    };
+    // The round rocks are the only thing moving, their bitmasks identify the state
+    fn load_after(&mut self, cycles: u64, tilts: &[Direction]) -> u64 {
+        let mut repetition_detector: HashMap<Vec<u128>, u64> = HashMap::new();
+        let mut loads = vec![];
+        for cycle in 0..=cycles {
+            if let Some(previous_cycle) = repetition_detector.insert(self.round.clone(), cycle) {
+                let period = cycle - previous_cycle;
+                return loads[(previous_cycle + (cycles - previous_cycle) % period) as usize];
+            }
+            loads.push(self.load());
+            if cycle < cycles {
+                for direction in tilts {
+                    self.tilt(*direction);
                 }
-            };
+            }
         }
+        *loads.last().unwrap()
     }
 }
 
-fn display_rocks(map: &Vec<Vec<Tile>>) -> String {
-    let mut formatted = String::new();
-    for i in map {
-        for j in i {
-            formatted += &format!(
-                "{}",
-                match j {
-                    Round => 'O',
-                    Square => '#',
-                    Empty => '.',
-                }
-            )
-            .to_string();
+fn get_input() -> Platform {
+    let input = fs::read_to_string(Path::new("./input/day14.input"))
+        .expect("Something went wrong with the input");
+    let mut platform = Platform {
+        width: 0,
+        round: vec![],
+        square: vec![],
+    };
+    for line in input.trim().lines() {
+        platform.width = line.len();
+        assert!(platform.width <= 128, "The platform is too wide");
+        let (mut round, mut square) = (0, 0);
+        for (col_no, tile) in line.chars().enumerate() {
+            match tile {
+                '.' => {}
+                'O' => round |= 1 << col_no,
+                '#' => square |= 1 << col_no,
+                _ => unreachable!(),
+            }
         }
-        formatted += "\n";
+        platform.round.push(round);
+        platform.square.push(square);
     }
-    formatted
+    platform
 }
 
-fn get_weight(map: &Vec<Vec<Tile>>) -> usize {
-    let height = map.len();
-    map.iter()
-        .enumerate()
-        .map(|(line_no, line)| {
-            (height - line_no) * line.iter().filter(|tile| **tile == Round).count()
-        })
-        .sum()
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let mut platform = get_input();
+    platform.tilt(North);
+
+    println!(
+        "The total load of the north support beam is {}",
+        platform.load()
+    );
+
+    Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let mut input = get_input();
-
-    let mut repetition_detector = HashMap::new();
-    let mut weights = vec![];
-    let mut repetiting_weights = vec![];
-    let mut repetition_start = 0;
-    let directions = [North, West, South, East];
-    for cycle in 0.. {
-        for direction in directions.iter() {
-            move_rocks(&mut input, *direction);
-        }
-        if let Some(previous_cycle) = repetition_detector.insert(display_rocks(&input), cycle) {
-            repetiting_weights = weights.iter().skip(previous_cycle).collect();
-            repetition_start = previous_cycle;
-            break;
-        }
-        weights.push(get_weight(&input));
-    }
+    let mut platform = get_input();
+
+    // --cycles <count> and --tilts <directions> change the spin cycle, such as --cycles 3 --tilts NWSE
+    let cycles = match cli::value("--cycles") {
+        Some(cycles) => cycles.parse::<u64>()?,
+        None => 1_000_000_000,
+    };
+    let tilts = match cli::value("--tilts") {
+        Some(tilts) => tilts
+            .chars()
+            .map(|direction| match direction {
+                'N' => Ok(North),
+                'E' => Ok(East),
+                'S' => Ok(South),
+                'W' => Ok(West),
+                _ => Err(format!("Unknown direction {}", direction)),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![North, West, South, East],
+    };
 
     println!(
-        "After 1 000 000 000 cycles, the total load of the north support beam is {}",
-        repetiting_weights[(999_999_999 - repetition_start) % repetiting_weights.len()] // We count cycle 0, 0..=999_999_999 is 1 000 000 000 cycle, here is my off by one :D
+        "After {} cycles, the total load of the north support beam is {}",
+        cycles,
+        platform.load_after(cycles, &tilts)
     );
 
     Ok(())
@@ -1,89 +1,161 @@
-use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::fs;
-
+use std::hash::Hasher;
 use std::path::Path;
 
+use crate::cli;
+
 fn get_input() -> Vec<String> {
     let input = fs::read_to_string(Path::new("./input/day15.input"))
         .expect("Something went wrong with the input");
-    return input
+    input
         .trim()
         .split(',')
         .map(|elem| elem.to_string())
-        .collect();
+        .collect()
+}
+
+// The HASH algorithm, wrapping around a byte is the same as the puzzle's modulo 256
+#[derive(Debug, Default)]
+pub struct HashAlgorithm {
+    state: u8,
+}
+
+impl Hasher for HashAlgorithm {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state = self.state.wrapping_add(*byte).wrapping_mul(17);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state as u64
+    }
+}
+
+fn hash(text: &str) -> usize {
+    let mut hasher = HashAlgorithm::default();
+    hasher.write(text.as_bytes());
+    hasher.finish() as usize
+}
+
+// The HASHMAP: 256 boxes of labelled lenses, each box keeping its lenses in insertion order
+#[derive(Debug)]
+pub struct LensMap {
+    boxes: Vec<Vec<(String, usize)>>,
+}
+
+impl LensMap {
+    pub fn new() -> Self {
+        LensMap {
+            boxes: vec![vec![]; 256],
+        }
+    }
+
+    // Replaces the lens with the same label in place, returns its focal length
+    pub fn insert(&mut self, label: &str, focal_length: usize) -> Option<usize> {
+        let lenses = &mut self.boxes[hash(label)];
+        match lenses.iter_mut().find(|(name, _)| name == label) {
+            Some((_, focal)) => Some(std::mem::replace(focal, focal_length)),
+            None => {
+                lenses.push((label.to_string(), focal_length));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, label: &str) -> Option<usize> {
+        let lenses = &mut self.boxes[hash(label)];
+        let position = lenses.iter().position(|(name, _)| name == label)?;
+        Some(lenses.remove(position).1)
+    }
+
+    pub fn get(&self, label: &str) -> Option<usize> {
+        self.boxes[hash(label)]
+            .iter()
+            .find(|(name, _)| name == label)
+            .map(|(_, focal)| *focal)
+    }
+
+    // Box number, slot number in the box, label and focal length of each lens, box after box
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &str, usize)> {
+        self.boxes.iter().enumerate().flat_map(|(box_no, lenses)| {
+            lenses
+                .iter()
+                .enumerate()
+                .map(move |(slot, (label, focal))| (box_no, slot, label.as_str(), *focal))
+        })
+    }
+
+    pub fn focusing_power(&self) -> usize {
+        self.iter()
+            .map(|(box_no, slot, _, focal)| (box_no + 1) * (slot + 1) * focal)
+            .sum()
+    }
+
+    // Steps are either label=focal or label-
+    fn apply(&mut self, step: &str) -> Result<(), Box<dyn Error + 'static>> {
+        if let Some((label, focal_length)) = step.split_once('=') {
+            self.insert(label, focal_length.parse()?);
+        } else if let Some(label) = step.strip_suffix('-') {
+            self.remove(label);
+        } else {
+            return Err(format!("Invalid step {}", step).into());
+        }
+        Ok(())
+    }
+}
+
+// Only the boxes holding lenses, as in the puzzle
+impl fmt::Display for LensMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (box_no, lenses) in self.boxes.iter().enumerate() {
+            if !lenses.is_empty() {
+                write!(f, "Box {}:", box_no)?;
+                for (label, focal) in lenses {
+                    write!(f, " [{} {}]", label, focal)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
     let input_sequence = get_input();
     println!(
         "Total hash value of the input: {}",
-        input_sequence.iter().fold(0, |total, step| {
-            total
-                + step
-                    .chars()
-                    .fold(0, |acc, elem| ((acc + (elem as usize)) * 17) % 256)
-        })
+        input_sequence.iter().map(|step| hash(step)).sum::<usize>()
     );
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
     let input_sequence = get_input();
-    let mut boxes = HashMap::<usize, Vec<(String, usize)>>::new();
+    let mut boxes = LensMap::new();
+    let trace = cli::flag("--trace");
 
     for step in input_sequence {
-        let label_chars = step.chars().take_while(|ch| ch.is_ascii_alphabetic());
-        let mut iter_step = step.chars().skip_while(|ch| ch.is_ascii_alphabetic());
-        let symbol = iter_step.next().unwrap();
-        let remaining = iter_step.collect::<String>();
-
-        let box_index = label_chars
-            .clone()
-            .fold(0, |acc, ch| (((acc + (ch as usize)) * 17) % 256));
-
-        let label: String = label_chars.collect();
-
-        match symbol {
-            '-' => {
-                if let Some(lenses) = boxes.get_mut(&box_index) {
-                    if let Some(position) = lenses
-                        .iter()
-                        .position(|(lens_label, _)| &label == lens_label)
-                    {
-                        lenses.remove(position);
-                    }
-                }
-            }
-            '=' => {
-                let lens_focal = remaining.parse::<usize>().unwrap();
-                boxes
-                    .entry(box_index)
-                    .and_modify(|lenses| {
-                        if let Some(position) = lenses.iter().position(|(name, _)| name == &label) {
-                            lenses[position].1 = lens_focal;
-                        } else {
-                            lenses.push((label.clone(), lens_focal))
-                        }
-                    })
-                    .or_insert(vec![(label.clone(), lens_focal)]);
-            }
-            _ => unreachable!(),
+        boxes.apply(&step)?;
+        // --trace dumps the boxes after every step
+        if trace {
+            println!("After \"{}\":\n{}", step, boxes);
         }
     }
 
-    let focal_power = boxes.iter().fold(0, |x, (box_index, lenses)| {
-        x + lenses
-            .iter()
-            .enumerate()
-            .fold(0, |y, (index, (_, focal_strength))| {
-                y + (focal_strength * (index + 1) * (box_index + 1))
-            })
-    });
+    // --lens <label> shows the focal length of a lens once the sequence is done
+    if let Some(label) = cli::value("--lens") {
+        match boxes.get(&label) {
+            Some(focal) => println!("Lens {} has a focal length of {}", label, focal),
+            None => println!("Lens {} is not in any box", label),
+        }
+    }
 
     println!(
         "After completing the initialisation sequence, the total focal power is: {}",
-        focal_power
+        boxes.focusing_power()
     );
 
     Ok(())
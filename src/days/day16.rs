@@ -1,7 +1,9 @@
-use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
 use std::path::Path;
+use std::thread;
+
+use crate::cli;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Direction {
@@ -96,7 +98,7 @@ impl Beam {
         }
     }
 
-    fn tile_effect(mut self, map: &Vec<Vec<char>>) -> (Option<Self>, Option<Self>) {
+    fn tile_effect(mut self, map: &[Vec<char>]) -> (Option<Self>, Option<Self>) {
         let (height, width) = (map.len(), map[0].len());
         let tile = map[self.position.0][self.position.1];
         let other_beam = self.rotate_beam(tile);
@@ -122,18 +124,61 @@ fn get_input() -> Vec<Vec<char>> {
         .collect()
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let map = get_input();
-    let mut cycle_detector: HashSet<((usize, usize), Direction)> = HashSet::new();
-    let mut visited: HashSet<(usize, usize)> = HashSet::new();
-    let mut beams = vec![Beam::new((0, 0), Right)];
+// Flat bitset of the (cell, direction) pairs a beam went through, 4 bits per cell
+struct Energized {
+    width: usize,
+    bits: Vec<u64>,
+}
+
+impl Energized {
+    fn new(width: usize, height: usize) -> Self {
+        Energized {
+            width,
+            bits: vec![0; (width * height * 4).div_ceil(64)],
+        }
+    }
+
+    // Returns false if a beam already went through this cell in this direction
+    fn insert(&mut self, beam: &Beam) -> bool {
+        let index = (beam.position.0 * self.width + beam.position.1) * 4 + beam.direction as usize;
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        let inserted = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        inserted
+    }
+
+    fn directions(&self, position: (usize, usize)) -> Vec<Direction> {
+        let cell = (position.0 * self.width + position.1) * 4;
+        [Up, Down, Left, Right]
+            .into_iter()
+            .filter(|direction| {
+                let index = cell + *direction as usize;
+                self.bits[index / 64] & 1 << (index % 64) != 0
+            })
+            .collect()
+    }
+
+    // Cells are 4-bit aligned, a cell is energized as soon as one of its bits is set
+    fn count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| {
+                let cells = word | word >> 1;
+                ((cells | cells >> 2) & 0x1111_1111_1111_1111).count_ones() as usize
+            })
+            .sum()
+    }
+}
+
+fn energize(map: &[Vec<char>], start: (usize, usize), direction: Direction) -> Energized {
+    let mut energized = Energized::new(map[0].len(), map.len());
+    let mut beams = vec![Beam::new(start, direction)];
 
     while let Some(beam) = beams.pop() {
-        if !cycle_detector.insert((beam.position, beam.direction)) {
+        if !energized.insert(&beam) {
             continue;
         }
-        visited.insert(beam.position);
-        let moved_beams = beam.tile_effect(&map);
+        let moved_beams = beam.tile_effect(map);
         if let Some(beam) = moved_beams.0 {
             beams.push(beam);
         }
@@ -141,92 +186,100 @@ pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
             beams.push(beam);
         }
     }
+    energized
+}
+
+// Energized tiles as #, or in the puzzle's way: empty tiles show the beam going through, or how many of them
+fn render(map: &[Vec<char>], energized: &Energized, arrows: bool) -> String {
+    let mut formatted = String::new();
+    for (line_no, line) in map.iter().enumerate() {
+        for (col_no, tile) in line.iter().enumerate() {
+            let directions = energized.directions((line_no, col_no));
+            formatted.push(match (arrows, *tile, &directions[..]) {
+                (_, _, []) => '.',
+                (false, _, _) => '#',
+                (true, '.', [direction]) => match direction {
+                    Up => '^',
+                    Down => 'v',
+                    Left => '<',
+                    Right => '>',
+                },
+                (true, '.', _) => char::from_digit(directions.len() as u32, 10).unwrap(),
+                (true, tile, _) => tile,
+            });
+        }
+        formatted += "\n";
+    }
+    formatted
+}
+
+// Binary PPM image, the more beam directions go through a tile, the brighter it is
+fn to_ppm(map: &[Vec<char>], energized: &Energized) -> Vec<u8> {
+    let mut image = format!("P6\n{} {}\n255\n", map[0].len(), map.len()).into_bytes();
+    for line_no in 0..map.len() {
+        for col_no in 0..map[0].len() {
+            let intensity = (energized.directions((line_no, col_no)).len() * 255 / 4) as u8;
+            image.extend([intensity, intensity, 0]);
+        }
+    }
+    image
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let map = get_input();
+    let energized = energize(&map, (0, 0), Right);
+
+    // --render shows the energized tiles, --arrows the beams going through them
+    if cli::flag("--render") || cli::flag("--arrows") {
+        print!("{}", render(&map, &energized, cli::flag("--arrows")));
+    }
+    // --ppm <path> exports the energy of each tile as an image
+    if let Some(path) = cli::value("--ppm") {
+        fs::write(path, to_ppm(&map, &energized))?;
+    }
 
-    println!("{} tiles ends up being energized", visited.len());
+    println!("{} tiles ends up being energized", energized.count());
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
     let map = get_input();
     let (height, width) = (map.len(), map[0].len());
-    let mut max_energy = 0;
 
-    for start_line in 0..height {
-        let mut cycle_detector: HashSet<((usize, usize), Direction)> = HashSet::new();
-        let mut visited: HashSet<(usize, usize)> = HashSet::new();
-        let mut beams = vec![Beam::new((start_line, 0), Right)];
+    let starts: Vec<((usize, usize), Direction)> = (0..height)
+        .flat_map(|line| [((line, 0), Right), ((line, width - 1), Left)])
+        .chain((0..width).flat_map(|col| [((0, col), Down), ((height - 1, col), Up)]))
+        .collect();
 
-        while let Some(beam) = beams.pop() {
-            if !cycle_detector.insert((beam.position, beam.direction)) {
-                continue;
-            }
-            visited.insert(beam.position);
-            let moved_beams = beam.tile_effect(&map);
-            if let Some(beam) = moved_beams.0 {
-                beams.push(beam);
-            }
-            if let Some(beam) = moved_beams.1 {
-                beams.push(beam);
-            }
-        }
-        max_energy = max_energy.max(visited.len());
-        cycle_detector.clear();
-        visited.clear();
-        beams.push(Beam::new((start_line, width - 1), Left));
-        while let Some(beam) = beams.pop() {
-            if !cycle_detector.insert((beam.position, beam.direction)) {
-                continue;
-            }
-            visited.insert(beam.position);
-            let moved_beams = beam.tile_effect(&map);
-            if let Some(beam) = moved_beams.0 {
-                beams.push(beam);
-            }
-            if let Some(beam) = moved_beams.1 {
-                beams.push(beam);
-            }
-        }
-        max_energy = max_energy.max(visited.len());
-    }
-
-    for start_col in 0..width {
-        let mut cycle_detector: HashSet<((usize, usize), Direction)> = HashSet::new();
-        let mut visited: HashSet<(usize, usize)> = HashSet::new();
-        let mut beams = vec![Beam::new((0, start_col), Down)];
-
-        while let Some(beam) = beams.pop() {
-            if !cycle_detector.insert((beam.position, beam.direction)) {
-                continue;
-            }
-            visited.insert(beam.position);
-            let moved_beams = beam.tile_effect(&map);
-            if let Some(beam) = moved_beams.0 {
-                beams.push(beam);
-            }
-            if let Some(beam) = moved_beams.1 {
-                beams.push(beam);
-            }
-        }
-        max_energy = max_energy.max(visited.len());
-        cycle_detector.clear();
-        visited.clear();
-        beams.push(Beam::new((height - 1, start_col), Up));
-        while let Some(beam) = beams.pop() {
-            if !cycle_detector.insert((beam.position, beam.direction)) {
-                continue;
-            }
-            visited.insert(beam.position);
-            let moved_beams = beam.tile_effect(&map);
-            if let Some(beam) = moved_beams.0 {
-                beams.push(beam);
-            }
-            if let Some(beam) = moved_beams.1 {
-                beams.push(beam);
-            }
-        }
-        max_energy = max_energy.max(visited.len());
-    }
+    // Every thread scans its own share of the edges
+    let threads = thread::available_parallelism().map_or(1, |count| count.get());
+    let chunk_size = starts.len().div_ceil(threads);
+    let (max_energy, start, direction) = thread::scope(|scope| {
+        let handles: Vec<_> = starts
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let map = &map;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&(start, direction)| {
+                            (energize(map, start, direction).count(), start, direction)
+                        })
+                        .max_by_key(|(energy, _, _)| *energy)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .max_by_key(|(energy, _, _)| *energy)
+            .unwrap()
+    });
 
     println!("{} tiles ends up being energized", max_energy);
+    println!(
+        "The best entry point is line {}, column {}, going {:?}",
+        start.0, start.1, direction
+    );
     Ok(())
 }
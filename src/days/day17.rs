@@ -71,7 +71,7 @@ fn least_heat(
         .collect();
 
     while !to_visit.is_empty() {
-        to_visit.sort_unstable_by(|tuple_a, tuple_b| tuple_b.1.cmp(&tuple_a.1));
+        to_visit.sort_unstable_by_key(|tuple| std::cmp::Reverse(tuple.1));
         let (coord, total_heat, dir, steps) = to_visit.pop().unwrap();
 
         if coord == end && steps + 1 >= min_straight {
@@ -1,267 +1,645 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+use itertools::Itertools;
 use regex::Regex;
 
+use crate::cli;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Lower,
+    LowerOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+use Comparison::*;
+
+#[derive(Debug, Clone)]
+struct Rule {
+    category: String,
+    comparison: Comparison,
+    value: usize,
+    target: String,
+}
+
+#[derive(Debug, Clone)]
 struct Workflow {
-    rules: Vec<Box<Rule>>,
+    rules: Vec<Rule>,
     default: String,
 }
 
-type Rule = dyn Fn(&Parts) -> Option<String>;
-
-impl Workflow {
-    fn create_rule(
-        category: char,
-        operand: bool,
-        value: usize,
-        result: String,
-    ) -> impl Fn(&Parts) -> Option<String> {
-        move |x| {
-            let val = x.get(category);
-            let compare = if operand { val > value } else { val < value };
-            if compare {
-                Some(result.clone())
-            } else {
-                None
-            }
-        }
-    }
-}
+type Workflows = HashMap<String, Workflow>;
 
+// Ratings of a part, by category name (x, m, a and s for the puzzle)
 #[derive(Debug)]
 struct Parts {
-    x: usize,
-    m: usize,
-    a: usize,
-    s: usize,
+    ratings: Vec<(String, usize)>,
 }
 
 impl Parts {
-    fn get(&self, elem: char) -> usize {
-        match elem {
-            'x' => self.x,
-            'm' => self.m,
-            'a' => self.a,
-            's' => self.s,
-            _ => unreachable!(),
+    fn get(&self, category: &str) -> Option<usize> {
+        self.ratings
+            .iter()
+            .find(|(name, _)| name == category)
+            .map(|(_, rating)| *rating)
+    }
+
+    fn total(&self) -> usize {
+        self.ratings.iter().map(|(_, rating)| rating).sum()
+    }
+}
+
+impl fmt::Display for Parts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ratings: Vec<String> = self
+            .ratings
+            .iter()
+            .map(|(name, rating)| format!("{}={}", name, rating))
+            .collect();
+        write!(f, "{{{}}}", ratings.join(","))
+    }
+}
+
+// Hyper-rectangle of parts, with inclusive bounds for each category
+#[derive(Debug, Clone)]
+struct PartsRange {
+    bounds: Vec<(String, (u128, u128))>,
+}
+
+const DEFAULT_BOUNDS: (u128, u128) = (1, 4_000);
+
+impl PartsRange {
+    fn get(&self, category: &str) -> Option<(u128, u128)> {
+        self.bounds
+            .iter()
+            .find(|(name, _)| name == category)
+            .map(|(_, bounds)| *bounds)
+    }
+
+    fn get_mut(&mut self, category: &str) -> Option<&mut (u128, u128)> {
+        self.bounds
+            .iter_mut()
+            .find(|(name, _)| name == category)
+            .map(|(_, bounds)| bounds)
+    }
+
+    fn combinations(&self) -> u128 {
+        self.bounds
+            .iter()
+            .map(|(_, (min, max))| max - min + 1)
+            .product()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self.comparison {
+            Lower => "<",
+            LowerOrEqual => "<=",
+            Greater => ">",
+            GreaterOrEqual => ">=",
+            Equal => "==",
+        };
+        write!(
+            f,
+            "{}{}{}:{}",
+            self.category, symbol, self.value, self.target
+        )
+    }
+}
+
+// One workflow visited by a part, with the position of the rule that fired (None when the default was taken)
+struct TraceStep {
+    workflow: String,
+    rule: Option<(usize, Rule)>,
+    target: String,
+}
+
+struct Trace {
+    steps: Vec<TraceStep>,
+    accepted: bool,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in self.steps.iter() {
+            match &step.rule {
+                Some((index, rule)) => writeln!(
+                    f,
+                    "  {}: rule {} ({}) fired -> {}",
+                    step.workflow,
+                    index + 1,
+                    rule,
+                    step.target
+                )?,
+                None => writeln!(f, "  {}: default -> {}", step.workflow, step.target)?,
+            }
         }
+        write!(
+            f,
+            "  {}",
+            if self.accepted {
+                "Accepted"
+            } else {
+                "Rejected"
+            }
+        )
     }
 }
 
-fn get_input() -> (HashMap<String, Workflow>, Vec<Parts>) {
-    let reg_rule = Regex::new(r"([xmas])([<>])(\d+):(\w+)|(\w+)").unwrap();
-    let reg_part = Regex::new(r"\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)}").unwrap();
+impl Rule {
+    // Inclusive bounds of the values satisfying the comparison, None if no value can
+    fn accepted_values(&self) -> Option<(u128, u128)> {
+        let value = self.value as u128;
+        match self.comparison {
+            Lower => value.checked_sub(1).map(|max| (0, max)),
+            LowerOrEqual => Some((0, value)),
+            Greater => Some((value + 1, u128::MAX)),
+            GreaterOrEqual => Some((value, u128::MAX)),
+            Equal => Some((value, value)),
+        }
+    }
+
+    // A part without a rating in the rule category never matches it
+    fn matches(&self, part: &Parts) -> bool {
+        match (part.get(&self.category), self.accepted_values()) {
+            (Some(value), Some((min, max))) => min <= value as u128 && value as u128 <= max,
+            _ => false,
+        }
+    }
+
+    // Split a range between the part matching the rule, and the (up to two) parts that don't
+    fn split(&self, range: PartsRange) -> (Option<PartsRange>, Vec<PartsRange>) {
+        let (Some((min, max)), Some((accepted_min, accepted_max))) =
+            (range.get(&self.category), self.accepted_values())
+        else {
+            return (None, vec![range]);
+        };
+
+        let with_bounds = |bounds: (u128, u128)| {
+            let mut range = range.clone();
+            if let Some(category_bounds) = range.get_mut(&self.category) {
+                *category_bounds = bounds;
+            }
+            range
+        };
+
+        let matching = if accepted_min.max(min) <= accepted_max.min(max) {
+            Some(with_bounds((accepted_min.max(min), accepted_max.min(max))))
+        } else {
+            None
+        };
+        let mut remaining = vec![];
+        if min < accepted_min {
+            remaining.push(with_bounds((min, max.min(accepted_min - 1))));
+        }
+        if accepted_max < max {
+            remaining.push(with_bounds((min.max(accepted_max + 1), max)));
+        }
+        (matching, remaining)
+    }
+}
+
+fn get_input() -> (Workflows, Vec<Parts>) {
+    let reg_rule = Regex::new(r"(\w+)(<=|>=|==|<|>)(\d+):(\w+)|(\w+)").unwrap();
+    let reg_rating = Regex::new(r"(\w+)=(\d+)").unwrap();
     let input = fs::read_to_string(Path::new("./input/day19.input"))
-        .expect("Something went wrong with the input");
-    let mut inputs = input.split("\r\n\r\n");
-    let rules = inputs
+        .expect("Something went wrong with the input")
+        .replace("\r\n", "\n");
+    let mut inputs = input.split("\n\n");
+    let workflows = inputs
         .next()
         .unwrap()
         .trim()
         .lines()
         .map(|line| {
-            let mut key = String::new();
+            let mut name = String::new();
             let mut workflow = Workflow {
                 rules: vec![],
                 default: String::new(),
             };
             for (index, cap) in reg_rule.captures_iter(line).enumerate() {
                 if index == 0 {
-                    key = cap[5].to_string();
+                    name = cap[5].to_string();
                     continue;
                 }
                 if cap.get(1).is_some() {
-                    let category = cap[1].chars().next().unwrap();
-                    let operand = &cap[2] == ">";
-                    let value = cap[3].parse::<usize>().unwrap();
-                    let result = cap[4].to_string();
-                    let rule = Workflow::create_rule(category, operand, value, result);
-                    workflow.rules.push(Box::new(rule));
+                    let comparison = match &cap[2] {
+                        "<" => Lower,
+                        "<=" => LowerOrEqual,
+                        ">" => Greater,
+                        ">=" => GreaterOrEqual,
+                        "==" => Equal,
+                        _ => unreachable!(),
+                    };
+                    workflow.rules.push(Rule {
+                        category: cap[1].to_string(),
+                        comparison,
+                        value: cap[3].parse().unwrap(),
+                        target: cap[4].to_string(),
+                    });
                 } else {
                     workflow.default = cap[5].to_string();
                 }
             }
-            (key, workflow)
+            (name, workflow)
         })
         .collect();
-    let parts: Vec<Parts> = reg_part
-        .captures_iter(inputs.next().unwrap())
-        .map(|cap| Parts {
-            x: cap[1].parse().unwrap(),
-            m: cap[2].parse().unwrap(),
-            a: cap[3].parse().unwrap(),
-            s: cap[4].parse().unwrap(),
+    let parts: Vec<Parts> = inputs
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .lines()
+        .map(|line| Parts {
+            ratings: reg_rating
+                .captures_iter(line)
+                .map(|cap| (cap[1].to_string(), cap[2].parse().unwrap()))
+                .collect(),
         })
         .collect();
-    (rules, parts)
+    (workflows, parts)
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let (workflows, parts) = get_input();
-    let mut total_rating_number = 0;
-
-    for part in parts {
-        let mut workflow_id = String::from("in");
-        loop {
-            let workflow = workflows.get(&workflow_id).unwrap();
-            for rule in workflow.rules.iter() {
-                if let Some(result) = rule(&part) {
-                    workflow_id = result;
-                    break;
-                } else {
-                    workflow_id = workflow.default.clone();
+// Every category rated by the parts or tested by the rules, bounded by 1..=4000 unless overridden
+// through --bounds, such as `--bounds x=1..4000,m=0..100`
+fn get_full_range(
+    workflows: &Workflows,
+    parts: &[Parts],
+) -> Result<PartsRange, Box<dyn Error + 'static>> {
+    let categories = parts
+        .iter()
+        .flat_map(|part| part.ratings.iter().map(|(name, _)| name))
+        .chain(
+            workflows
+                .values()
+                .flat_map(|workflow| workflow.rules.iter().map(|rule| &rule.category))
+                .sorted(),
+        )
+        .unique();
+    let mut full_range = PartsRange {
+        bounds: categories
+            .map(|name| (name.clone(), DEFAULT_BOUNDS))
+            .collect(),
+    };
+
+    if let Some(overrides) = cli::value("--bounds") {
+        for category_bounds in overrides.split(',') {
+            let (name, min, max) = category_bounds
+                .split_once('=')
+                .and_then(|(name, bounds)| {
+                    let (min, max) = bounds.split_once("..")?;
+                    Some((name, min.parse::<u128>().ok()?, max.parse::<u128>().ok()?))
+                })
+                .ok_or_else(|| format!("Invalid category bounds: {}", category_bounds))?;
+            if min > max {
+                return Err(format!("Empty category bounds: {}", category_bounds).into());
+            }
+            match full_range.get_mut(name) {
+                Some(bounds) => *bounds = (min, max),
+                None => full_range.bounds.push((name.to_string(), (min, max))),
+            }
+        }
+    }
+
+    Ok(full_range)
+}
+
+fn explain(workflows: &Workflows, part: &Parts) -> Trace {
+    let mut steps = vec![];
+    let mut workflow_id = String::from("in");
+    loop {
+        match workflow_id.as_str() {
+            "A" => {
+                return Trace {
+                    steps,
+                    accepted: true,
                 }
             }
-            if &workflow_id == "A" {
-                total_rating_number += part.x + part.m + part.a + part.s;
-                break;
-            } else if &workflow_id == "R" {
-                break;
+            "R" => {
+                return Trace {
+                    steps,
+                    accepted: false,
+                }
             }
+            _ => {}
         }
+        let workflow = workflows.get(&workflow_id).unwrap();
+        let rule = workflow
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matches(part))
+            .map(|(index, rule)| (index, rule.clone()));
+        let target = match &rule {
+            Some((_, rule)) => rule.target.clone(),
+            None => workflow.default.clone(),
+        };
+        steps.push(TraceStep {
+            workflow: workflow_id,
+            rule,
+            target: target.clone(),
+        });
+        workflow_id = target;
     }
-    println!(
-        "The total rating numbers of every good parts is {}",
-        total_rating_number
-    );
-    Ok(())
 }
 
-struct Node {
-    rules: Vec<TestNode>,
-    default: String,
+// Distribute a range between the rules of a workflow, None standing for the default
+fn route_range(workflow: &Workflow, range: PartsRange) -> Vec<(Option<usize>, PartsRange)> {
+    let mut routed = vec![];
+    let mut ranges_no = vec![range];
+
+    for (index, rule) in workflow.rules.iter().enumerate() {
+        let mut next_ranges_no = vec![];
+        for range in ranges_no {
+            let (range_yes, mut range_no) = rule.split(range);
+            if let Some(range_yes) = range_yes {
+                routed.push((Some(index), range_yes));
+            }
+            next_ranges_no.append(&mut range_no);
+        }
+        ranges_no = next_ranges_no;
+    }
+    routed.extend(ranges_no.into_iter().map(|range| (None, range)));
+    routed
 }
 
-struct TestNode {
-    category: char,
-    operand: bool,
-    value: u128,
-    goal: String,
+// Disjoint accepted hyper-rectangles, along with the workflows leading to each of them
+fn dfs(
+    workflows: &Workflows,
+    current: &str,
+    range: PartsRange,
+    path: &mut Vec<String>,
+    accepted: &mut Vec<(Vec<String>, PartsRange)>,
+) {
+    if current == "A" {
+        accepted.push((path.clone(), range));
+        return;
+    } else if current == "R" {
+        return;
+    }
+    let workflow = workflows.get(current).unwrap();
+
+    path.push(current.to_string());
+    for (rule, range) in route_range(workflow, range) {
+        let target = rule.map_or(&workflow.default, |index| &workflow.rules[index].target);
+        dfs(workflows, target, range, path, accepted);
+    }
+    path.pop();
 }
 
-#[derive(Debug, Clone, Copy)]
-struct XMASRange {
-    x: (u128, u128),
-    m: (u128, u128),
-    a: (u128, u128),
-    s: (u128, u128),
+fn accepted_ranges(
+    workflows: &Workflows,
+    full_range: PartsRange,
+) -> Vec<(Vec<String>, PartsRange)> {
+    let mut accepted = vec![];
+    dfs(workflows, "in", full_range, &mut vec![], &mut accepted);
+    accepted
 }
 
-fn get_input_part_2() -> HashMap<String, Node> {
-    let reg_rule = Regex::new(r"([xmas])([<>])(\d+):(\w+)|(\w+)").unwrap();
-    let input = fs::read_to_string(Path::new("./input/day19.input"))
-        .expect("Something went wrong with the input");
-    let rules_input = input.split("\r\n\r\n").next().unwrap();
+fn to_csv(accepted: &[(Vec<String>, PartsRange)], full_range: &PartsRange) -> String {
+    let mut csv = String::from("path");
+    for (name, _) in full_range.bounds.iter() {
+        csv += &format!(",{}_min,{}_max", name, name);
+    }
+    csv += ",combinations\n";
 
-    rules_input
-        .lines()
-        .map(|line| {
-            let mut node = Node {
-                rules: vec![],
-                default: String::new(),
-            };
-            let mut name = String::new();
-            for (index, cap) in reg_rule.captures_iter(line).enumerate() {
-                if index == 0 {
-                    name = cap[5].to_string();
-                    continue;
-                }
-                if cap.get(1).is_some() {
-                    let category = cap[1].chars().next().unwrap();
-                    let operand = &cap[2] == ">";
-                    let value = cap[3].parse::<u128>().unwrap();
-                    let goal = cap[4].to_string();
-                    node.rules.push(TestNode {
-                        category,
-                        operand,
-                        value,
-                        goal,
-                    });
-                } else {
-                    node.default = cap[5].to_string();
-                }
-            }
-            (name, node)
-        })
-        .collect()
+    for (path, range) in accepted {
+        csv += &path.join(">");
+        for (_, (min, max)) in range.bounds.iter() {
+            csv += &format!(",{},{}", min, max);
+        }
+        csv += &format!(",{}\n", range.combinations());
+    }
+    csv
+}
+
+enum Issue {
+    MissingStart,
+    UndefinedTarget {
+        workflow: String,
+        target: String,
+    },
+    Cycle(Vec<String>),
+    Unreachable(String),
+    ShadowedRule {
+        workflow: String,
+        rule: Option<usize>,
+    },
 }
 
-fn dfs(map: &HashMap<String, Node>, current: String, range: XMASRange) -> u128 {
-    if &current == "A" {
-        let total = (range.x.1 - range.x.0 + 1)
-            * (range.m.1 - range.m.0 + 1)
-            * (range.a.1 - range.a.0 + 1)
-            * (range.s.1 - range.s.0 + 1);
-        return total;
-    } else if &current == "R" {
-        return 0;
+use Issue::*;
+
+impl Issue {
+    // Those would make the part routing loop forever or panic
+    fn is_blocking(&self) -> bool {
+        matches!(self, MissingStart | UndefinedTarget { .. } | Cycle(_))
     }
-    let mut total = 0;
-    let node = map.get(&current).unwrap();
-    let mut range_no = range;
-
-    for rule in node.rules.iter() {
-        let mut range_yes = range_no;
-        match rule.category {
-            'x' => {
-                if rule.operand {
-                    range_yes.x.0 = rule.value + 1;
-                    range_no.x.1 = rule.value;
-                } else {
-                    range_yes.x.1 = rule.value - 1;
-                    range_no.x.0 = rule.value;
-                }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissingStart => write!(f, "No workflow named in to start from"),
+            UndefinedTarget { workflow, target } => {
+                write!(
+                    f,
+                    "Workflow {} sends parts to unknown workflow {}",
+                    workflow, target
+                )
             }
-            'm' => {
-                if rule.operand {
-                    range_yes.m.0 = rule.value + 1;
-                    range_no.m.1 = rule.value;
-                } else {
-                    range_yes.m.1 = rule.value - 1;
-                    range_no.m.0 = rule.value;
-                }
+            Cycle(path) => write!(f, "Workflows form a cycle: {}", path.join(" -> ")),
+            Unreachable(workflow) => write!(f, "Workflow {} can never be reached", workflow),
+            ShadowedRule {
+                workflow,
+                rule: Some(index),
+            } => write!(
+                f,
+                "Rule {} of workflow {} is shadowed by earlier rules",
+                index + 1,
+                workflow
+            ),
+            ShadowedRule {
+                workflow,
+                rule: None,
+            } => write!(
+                f,
+                "Default of workflow {} is shadowed by its rules",
+                workflow
+            ),
+        }
+    }
+}
+
+fn targets(workflow: &Workflow) -> impl Iterator<Item = &String> {
+    workflow
+        .rules
+        .iter()
+        .map(|rule| &rule.target)
+        .chain(std::iter::once(&workflow.default))
+}
+
+fn find_cycles<'a>(
+    workflows: &'a Workflows,
+    current: &'a String,
+    path: &mut Vec<&'a String>,
+    done: &mut HashSet<&'a String>,
+    issues: &mut Vec<Issue>,
+) {
+    if let Some(start) = path.iter().position(|workflow| *workflow == current) {
+        let mut cycle: Vec<String> = path[start..].iter().map(|name| name.to_string()).collect();
+        cycle.push(current.clone());
+        issues.push(Cycle(cycle));
+        return;
+    }
+    if !done.insert(current) {
+        return;
+    }
+    if let Some(workflow) = workflows.get(current) {
+        path.push(current);
+        for target in targets(workflow) {
+            find_cycles(workflows, target, path, done, issues);
+        }
+        path.pop();
+    }
+}
+
+fn validate(workflows: &Workflows, full_range: &PartsRange) -> Vec<Issue> {
+    let mut issues = vec![];
+    let start = String::from("in");
+
+    if !workflows.contains_key(&start) {
+        issues.push(MissingStart);
+    }
+
+    for (name, workflow) in workflows.iter().sorted_by_key(|(name, _)| *name) {
+        for target in targets(workflow) {
+            if target != "A" && target != "R" && !workflows.contains_key(target) {
+                issues.push(UndefinedTarget {
+                    workflow: name.clone(),
+                    target: target.clone(),
+                });
             }
-            'a' => {
-                if rule.operand {
-                    range_yes.a.0 = rule.value + 1;
-                    range_no.a.1 = rule.value;
-                } else {
-                    range_yes.a.1 = rule.value - 1;
-                    range_no.a.0 = rule.value;
-                }
+        }
+
+        // Every rule should be able to catch at least some parts of the whole range
+        let routed = route_range(workflow, full_range.clone());
+        for rule in workflow
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, _)| Some(index))
+            .chain([None])
+        {
+            if routed.iter().all(|(routed_rule, _)| *routed_rule != rule) {
+                issues.push(ShadowedRule {
+                    workflow: name.clone(),
+                    rule,
+                });
             }
-            's' => {
-                if rule.operand {
-                    range_yes.s.0 = rule.value + 1;
-                    range_no.s.1 = rule.value;
-                } else {
-                    range_yes.s.1 = rule.value - 1;
-                    range_no.s.0 = rule.value;
-                }
+        }
+    }
+
+    let mut done = HashSet::new();
+    find_cycles(workflows, &start, &mut vec![], &mut done, &mut issues);
+    for name in workflows.keys().sorted() {
+        if !done.contains(name) {
+            issues.push(Unreachable(name.clone()));
+        }
+    }
+
+    issues
+}
+
+fn check(workflows: &Workflows, full_range: &PartsRange) -> Result<(), Box<dyn Error + 'static>> {
+    let blocking: Vec<String> = validate(workflows, full_range)
+        .iter()
+        .filter(|issue| issue.is_blocking())
+        .map(|issue| issue.to_string())
+        .collect();
+    if blocking.is_empty() {
+        Ok(())
+    } else {
+        Err(blocking.join("; ").into())
+    }
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let (workflows, parts) = get_input();
+    let full_range = get_full_range(&workflows, &parts)?;
+    for issue in validate(&workflows, &full_range) {
+        println!("Warning: {}", issue);
+    }
+    check(&workflows, &full_range)?;
+
+    // --explain prints the route of every part, --explain <index> only the route of that part
+    if cli::flag("--explain") {
+        let selected = cli::value("--explain").and_then(|index| index.parse::<usize>().ok());
+        for (index, part) in parts.iter().enumerate() {
+            if selected.is_none_or(|selected| selected == index) {
+                println!("Part {} {}:", index, part);
+                println!("{}", explain(&workflows, part));
             }
-            _ => unreachable!(),
         }
-        total += dfs(map, rule.goal.clone(), range_yes);
     }
-    total += dfs(map, node.default.clone(), range_no);
-    total
+
+    let total_rating_number: usize = parts
+        .iter()
+        .filter(|part| explain(&workflows, part).accepted)
+        .map(|part| part.total())
+        .sum();
+
+    println!(
+        "The total rating numbers of every good parts is {}",
+        total_rating_number
+    );
+    Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let rules = get_input_part_2();
-    let total_combination: u128 = dfs(
-        &rules,
-        String::from("in"),
-        XMASRange {
-            x: (1, 4_000),
-            m: (1, 4_000),
-            a: (1, 4_000),
-            s: (1, 4_000),
-        },
-    );
+    let (workflows, parts) = get_input();
+    let full_range = get_full_range(&workflows, &parts)?;
+    check(&workflows, &full_range)?;
+    let accepted = accepted_ranges(&workflows, full_range.clone());
+
+    if let Some(path) = cli::value("--csv") {
+        fs::write(Path::new(&path), to_csv(&accepted, &full_range))?;
+    }
+    // --through <workflow> lists the accepted ranges going through that workflow
+    if let Some(workflow) = cli::value("--through") {
+        let through: Vec<_> = accepted
+            .iter()
+            .filter(|(path, _)| path.contains(&workflow))
+            .collect();
+        for (path, range) in through.iter() {
+            let bounds: Vec<String> = range
+                .bounds
+                .iter()
+                .map(|(name, (min, max))| format!("{}={}..{}", name, min, max))
+                .collect();
+            println!(
+                "{} [{}] via {}",
+                range.combinations(),
+                bounds.join(", "),
+                path.join(" -> ")
+            );
+        }
+        println!(
+            "{} combinations are accepted through {}",
+            through
+                .iter()
+                .map(|(_, range)| range.combinations())
+                .sum::<u128>(),
+            workflow
+        );
+    }
+
+    let total_combination: u128 = accepted.iter().map(|(_, range)| range.combinations()).sum();
     println!(
         "The total amount of valid combinations using current rules is {}",
         total_combination
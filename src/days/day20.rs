@@ -1,8 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+use itertools::Itertools;
+
+use crate::cli;
+use crate::dot::DotGraph;
+
 struct Broadcast {
     connected: Vec<String>,
 }
@@ -41,9 +47,42 @@ impl Conjuction {
     }
 }
 
+// Sends the opposite of every pulse it receives
+struct Inverter {
+    connected: Vec<String>,
+}
+
+impl Inverter {
+    fn new(connected: Vec<String>) -> Self {
+        Inverter { connected }
+    }
+}
+
+// Clock divider: only forwards one pulse out of every `period` it receives
+struct Counter {
+    period: usize,
+    count: usize,
+    connected: Vec<String>,
+}
+
+impl Counter {
+    fn new(period: usize, connected: Vec<String>) -> Self {
+        Counter {
+            period,
+            count: 0,
+            connected,
+        }
+    }
+}
+
 trait Output {
     fn output(&mut self, input: bool, _from: &str) -> Vec<(String, bool)>;
     fn update_connected(&mut self, connected: Vec<String>);
+    fn kind(&self) -> &'static str;
+    fn targets(&self) -> &[String];
+    // Internal memory of the module, as a fixed amount of bits
+    fn state(&self) -> Vec<bool>;
+    fn restore(&mut self, state: &[bool]);
 }
 
 impl Output for Broadcast {
@@ -55,6 +94,16 @@ impl Output for Broadcast {
             .collect()
     }
     fn update_connected(&mut self, _connected: Vec<String>) {}
+    fn kind(&self) -> &'static str {
+        "broadcaster"
+    }
+    fn targets(&self) -> &[String] {
+        &self.connected
+    }
+    fn state(&self) -> Vec<bool> {
+        vec![]
+    }
+    fn restore(&mut self, _state: &[bool]) {}
 }
 
 impl Output for FlipFlop {
@@ -71,6 +120,18 @@ impl Output for FlipFlop {
         }
     }
     fn update_connected(&mut self, _connected: Vec<String>) {}
+    fn kind(&self) -> &'static str {
+        "flip-flop"
+    }
+    fn targets(&self) -> &[String] {
+        &self.connected
+    }
+    fn state(&self) -> Vec<bool> {
+        vec![self.status]
+    }
+    fn restore(&mut self, state: &[bool]) {
+        self.status = state[0];
+    }
 }
 
 impl Output for Conjuction {
@@ -89,12 +150,93 @@ impl Output for Conjuction {
     fn update_connected(&mut self, connected: Vec<String>) {
         self.connecting = connected.into_iter().map(|key| (key, false)).collect();
     }
+    fn kind(&self) -> &'static str {
+        "conjunction"
+    }
+    fn targets(&self) -> &[String] {
+        &self.targets
+    }
+    fn state(&self) -> Vec<bool> {
+        self.connecting
+            .iter()
+            .sorted_by_key(|(from, _)| *from)
+            .map(|(_, signal)| *signal)
+            .collect()
+    }
+    fn restore(&mut self, state: &[bool]) {
+        for ((_, signal), bit) in self
+            .connecting
+            .iter_mut()
+            .sorted_by_key(|(from, _)| *from)
+            .zip(state)
+        {
+            *signal = *bit;
+        }
+    }
+}
+
+impl Output for Inverter {
+    fn output(&mut self, input: bool, _from: &str) -> Vec<(String, bool)> {
+        self.connected
+            .iter()
+            .cloned()
+            .map(|key| (key, !input))
+            .collect()
+    }
+    fn update_connected(&mut self, _connected: Vec<String>) {}
+    fn kind(&self) -> &'static str {
+        "inverter"
+    }
+    fn targets(&self) -> &[String] {
+        &self.connected
+    }
+    fn state(&self) -> Vec<bool> {
+        vec![]
+    }
+    fn restore(&mut self, _state: &[bool]) {}
+}
+
+impl Output for Counter {
+    fn output(&mut self, input: bool, _from: &str) -> Vec<(String, bool)> {
+        self.count = (self.count + 1) % self.period;
+        if self.count == 0 {
+            self.connected
+                .iter()
+                .cloned()
+                .map(|key| (key, input))
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+    fn update_connected(&mut self, _connected: Vec<String>) {}
+    fn kind(&self) -> &'static str {
+        "counter"
+    }
+    fn targets(&self) -> &[String] {
+        &self.connected
+    }
+    fn state(&self) -> Vec<bool> {
+        let bits = usize::BITS - (self.period - 1).leading_zeros();
+        (0..bits).map(|bit| self.count & (1 << bit) != 0).collect()
+    }
+    fn restore(&mut self, state: &[bool]) {
+        self.count = state
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| **bit)
+            .map(|(bit, _)| 1 << bit)
+            .sum();
+    }
 }
 
-fn get_input() -> HashMap<String, Box<dyn Output>> {
+type Modules = HashMap<String, Box<dyn Output>>;
+
+// On top of the puzzle modules, `!name` is an inverter and `#3name` a counter with a period of 3
+fn get_input() -> Network {
     let input = fs::read_to_string(Path::new("./input/day20.input"))
         .expect("Something went wrong with the input");
-    let mut network: HashMap<String, Box<dyn Output>> = HashMap::new();
+    let mut network: Modules = HashMap::new();
     let mut connected: HashMap<String, Vec<String>> = HashMap::new();
     for line in input.trim().lines() {
         let split: Vec<_> = line.split(" -> ").collect();
@@ -112,7 +254,11 @@ fn get_input() -> HashMap<String, Box<dyn Output>> {
         }
         let mut parser = name.chars();
         let module_type = parser.next().unwrap();
-        let name: String = parser.collect();
+        let period: String = parser
+            .clone()
+            .take_while(|ch| ch.is_ascii_digit())
+            .collect();
+        let name: String = parser.skip(period.len()).collect();
         for target in targets.iter() {
             connected
                 .entry(target.to_string())
@@ -126,6 +272,13 @@ fn get_input() -> HashMap<String, Box<dyn Output>> {
             '%' => {
                 network.insert(name, Box::new(FlipFlop::new(targets)));
             }
+            '!' => {
+                network.insert(name, Box::new(Inverter::new(targets)));
+            }
+            '#' => {
+                let period = period.parse().unwrap_or(1).max(1);
+                network.insert(name, Box::new(Counter::new(period, targets)));
+            }
             _ => unreachable!(),
         }
     }
@@ -134,41 +287,285 @@ fn get_input() -> HashMap<String, Box<dyn Output>> {
             .entry(key)
             .and_modify(|node| node.update_connected(value));
     }
-    network
+    Network::new(network)
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let mut network = get_input();
-    let mut low_pulse = 0;
-    let mut high_pulse = 0;
-
-    for _ in 0..1000 {
-        low_pulse += 1;
-        let mut nodes = vec![(String::from("broadcaster"), false, String::from("button"))];
-        while !nodes.is_empty() {
-            let mut next_round = vec![];
-            for (node_name, signal, from) in nodes.iter() {
-                if !network.contains_key(node_name) {
-                    continue;
+// Modules are labelled with their type, untyped modules (such as rx) are highlighted
+fn to_dot(network: &Modules) -> DotGraph {
+    let mut graph = DotGraph::new("day20", true);
+    for (name, module) in network.iter().sorted_by_key(|(name, _)| *name) {
+        graph.node(name, Some(format!("{} ({})", name, module.kind())), false);
+        for target in module.targets() {
+            graph.edge(name, target, None, false);
+        }
+    }
+    for target in network
+        .values()
+        .flat_map(|module| module.targets())
+        .filter(|target| !network.contains_key(*target))
+        .unique()
+    {
+        graph.node(target, None, true);
+    }
+    graph
+}
+
+struct Pulse {
+    press: usize,
+    tick: usize,
+    from: String,
+    to: String,
+    high: bool,
+}
+
+impl fmt::Display for Pulse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = if self.high { "high" } else { "low" };
+        write!(f, "{} -{}-> {}", self.from, level, self.to)
+    }
+}
+
+#[derive(Default)]
+struct PressStats {
+    low: usize,
+    high: usize,
+    // Only filled when the network is tracing
+    pulses: Vec<Pulse>,
+}
+
+// State of every module packed into a bitvector, in module name order
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Snapshot {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+struct Network {
+    modules: Modules,
+    presses: usize,
+    tracing: bool,
+    initial: Snapshot,
+}
+
+impl Network {
+    fn new(modules: Modules) -> Self {
+        let mut network = Network {
+            modules,
+            presses: 0,
+            tracing: false,
+            initial: Snapshot {
+                bits: vec![],
+                len: 0,
+            },
+        };
+        network.initial = network.snapshot();
+        network
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot {
+            bits: vec![],
+            len: 0,
+        };
+        for (_, module) in self.modules.iter().sorted_by_key(|(name, _)| *name) {
+            for bit in module.state() {
+                if snapshot.len.is_multiple_of(64) {
+                    snapshot.bits.push(0);
                 }
-                let node = network.get_mut(node_name).unwrap();
-                let mut output: Vec<_> = node
-                    .output(*signal, from)
-                    .into_iter()
-                    .map(|(target, signal)| (target, signal, node_name.clone()))
-                    .collect();
-                if !output.is_empty() {
-                    if output[0].1 {
-                        high_pulse += output.len();
-                    } else {
-                        low_pulse += output.len();
-                    }
+                if bit {
+                    snapshot.bits[snapshot.len / 64] |= 1 << (snapshot.len % 64);
                 }
-                next_round.append(&mut output);
+                snapshot.len += 1;
+            }
+        }
+        snapshot
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        let mut position = 0;
+        for (_, module) in self.modules.iter_mut().sorted_by_key(|(name, _)| *name) {
+            let size = module.state().len();
+            let state: Vec<bool> = (position..position + size)
+                .map(|bit| snapshot.bits[bit / 64] & (1 << (bit % 64)) != 0)
+                .collect();
+            module.restore(&state);
+            position += size;
+        }
+    }
+
+    fn reset(&mut self) {
+        let initial = self.initial.clone();
+        self.restore(&initial);
+        self.presses = 0;
+    }
+
+    // Press until a state is seen twice, returns the press after which the cycle starts and its length
+    fn find_cycle(&mut self, limit: usize) -> Option<(usize, usize)> {
+        let mut seen: HashMap<Snapshot, usize> = HashMap::from([(self.snapshot(), self.presses)]);
+        for _ in 0..limit {
+            self.press();
+            if let Some(start) = seen.insert(self.snapshot(), self.presses) {
+                return Some((start, self.presses - start));
+            }
+        }
+        None
+    }
+
+    // Pulses are processed in the order they are sent, the tick being the number of modules crossed since the button
+    fn press(&mut self) -> PressStats {
+        let mut stats = PressStats::default();
+        let mut queue = VecDeque::from([(
+            String::from("button"),
+            String::from("broadcaster"),
+            false,
+            0,
+        )]);
+
+        while let Some((from, to, high, tick)) = queue.pop_front() {
+            if high {
+                stats.high += 1;
+            } else {
+                stats.low += 1;
+            }
+            if let Some(module) = self.modules.get_mut(&to) {
+                for (target, level) in module.output(high, &from) {
+                    queue.push_back((to.clone(), target, level, tick + 1));
+                }
+            }
+            if self.tracing {
+                stats.pulses.push(Pulse {
+                    press: self.presses,
+                    tick,
+                    from,
+                    to,
+                    high,
+                });
             }
-            nodes = next_round;
         }
+
+        self.presses += 1;
+        stats
+    }
+}
+
+// Short VCD identifiers, made of printable ASCII characters
+fn vcd_identifier(mut index: usize) -> String {
+    let mut identifier = String::new();
+    loop {
+        identifier.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return identifier;
+        }
+        index -= 1;
+    }
+}
+
+// Output level of every module over time, each propagation round being one time unit
+fn to_vcd(network: &Modules, pulses: &[Pulse]) -> String {
+    let identifiers: HashMap<&String, String> = network
+        .keys()
+        .sorted()
+        .enumerate()
+        .map(|(index, name)| (name, vcd_identifier(index)))
+        .collect();
+
+    let mut vcd = String::from("$timescale 1ns $end\n$scope module day20 $end\n");
+    for name in network.keys().sorted() {
+        vcd += &format!("$var wire 1 {} {} $end\n", identifiers[name], name);
+    }
+    vcd += "$upscope $end\n$enddefinitions $end\n#0\n$dumpvars\n";
+    for name in network.keys().sorted() {
+        vcd += &format!("0{}\n", identifiers[name]);
+    }
+    vcd += "$end\n";
+
+    let mut levels: HashMap<&String, bool> = HashMap::new();
+    let (mut press, mut offset, mut press_length) = (0, 0, 0);
+    let mut last_time = None;
+    for pulse in pulses.iter() {
+        if pulse.press != press {
+            press = pulse.press;
+            offset += press_length;
+            press_length = 0;
+        }
+        press_length = press_length.max(pulse.tick + 1);
+
+        let Some(identifier) = identifiers.get(&pulse.from) else {
+            continue;
+        };
+        let level = levels.entry(&pulse.from).or_insert(false);
+        if *level == pulse.high {
+            continue;
+        }
+        *level = pulse.high;
+
+        let time = offset + pulse.tick;
+        if last_time != Some(time) {
+            last_time = Some(time);
+            vcd += &format!("#{}\n", time);
+        }
+        vcd += &format!("{}{}\n", if pulse.high { 1 } else { 0 }, identifier);
     }
+    vcd
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let mut network = get_input();
+    if let Some(path) = cli::value("--dot") {
+        to_dot(&network.modules).write(&path)?;
+    }
+    // --trace prints every pulse, --vcd <path> saves the modules output levels as a waveform
+    let trace = cli::flag("--trace");
+    let vcd_path = cli::value("--vcd");
+    network.tracing = trace || vcd_path.is_some();
+
+    // Once the network state repeats, the following presses send the same pulses, no need to simulate them
+    let cycle = if network.tracing {
+        None
+    } else {
+        network.find_cycle(1000)
+    };
+    let (start, length) = match cycle {
+        Some((start, length)) => {
+            println!(
+                "The network state repeats every {} presses, starting after {} presses",
+                length, start
+            );
+            (start, length)
+        }
+        None => (0, 1000),
+    };
+    network.reset();
+
+    let mut pulses = vec![];
+    let mut press_counts = vec![];
+    for _ in 0..(start + length).min(1000) {
+        let mut stats = network.press();
+        press_counts.push((stats.low, stats.high));
+        pulses.append(&mut stats.pulses);
+    }
+    let (low_pulse, high_pulse) = (0..1000)
+        .map(|press| {
+            if press < start + length {
+                press_counts[press]
+            } else {
+                press_counts[start + (press - start) % length]
+            }
+        })
+        .fold((0, 0), |(low, high), counts| {
+            (low + counts.0, high + counts.1)
+        });
+
+    if trace {
+        for pulse in pulses.iter() {
+            println!("[press {} tick {}] {}", pulse.press + 1, pulse.tick, pulse);
+        }
+    }
+    if let Some(path) = vcd_path {
+        fs::write(Path::new(&path), to_vcd(&network.modules, &pulses))?;
+    }
+
     println!(
         "After 1000 button press, the multiplicated total number of pulses is: {}",
         low_pulse * high_pulse
@@ -16,7 +16,7 @@ struct Coordinate {
 }
 
 impl Coordinate {
-    fn neighbors(&self, map: &Vec<Vec<Tile>>) -> Vec<Coordinate> {
+    fn neighbors(&self, map: &[Vec<Tile>]) -> Vec<Coordinate> {
         let (height, width) = (map.len(), map[0].len());
         let mut neighbors = vec![];
         if self.line > 0 {
@@ -53,7 +53,7 @@ fn get_input() -> Vec<Brick> {
 type Tower = HashMap<usize, (HashSet<usize>, HashSet<usize>)>;
 
 fn get_tower(mut bricks: Vec<Brick>) -> Tower {
-    bricks.sort_by(|brick_a, brick_b| brick_a.start.z.cmp(&brick_b.start.z));
+    bricks.sort_by_key(|brick| brick.start.z);
 
     let mut highest = 0;
     let mut bump_map: HashMap<usize, HashMap<(usize, usize), usize>> = HashMap::new();
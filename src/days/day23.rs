@@ -1,8 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::path::Path;
 
+use crate::cli;
+use crate::dot::DotGraph;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum Tile {
     Flat,
@@ -60,249 +63,234 @@ fn get_input() -> Vec<Vec<Tile>> {
         .collect()
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let hike_map = get_input();
+struct JunctionGraph {
+    junctions: Vec<Coordinate>,
+    edges: Vec<Vec<(usize, usize)>>,
+    start: usize,
+    end: usize,
+}
 
-    let start_col = hike_map[0]
-        .iter()
-        .position(|tile| *tile == Flat)
-        .unwrap_or(0);
-    let mut hikers: Vec<(Hiker, HashSet<Coordinate>)> = vec![(
-        Hiker {
-            facing: Down,
-            position: Coordinate { x: 0, y: start_col },
-            step_count: 0,
+fn step(hike_map: &[Vec<Tile>], position: Coordinate, facing: Facing) -> Option<Coordinate> {
+    let next = match facing {
+        Up => Coordinate {
+            x: position.x.checked_sub(1)?,
+            y: position.y,
         },
-        HashSet::new(),
-    )];
-
-    let mut max_step = 0;
+        Left => Coordinate {
+            x: position.x,
+            y: position.y.checked_sub(1)?,
+        },
+        Down => Coordinate {
+            x: position.x + 1,
+            y: position.y,
+        },
+        Right => Coordinate {
+            x: position.x,
+            y: position.y + 1,
+        },
+    };
+    match hike_map.get(next.x).and_then(|line| line.get(next.y)) {
+        None | Some(Rock) => None,
+        _ => Some(next),
+    }
+}
 
-    while let Some((mut hiker, mut visited_coordinates)) = hikers.pop() {
-        loop {
-            let next_coordinate = match hiker.facing {
-                Up => Coordinate {
-                    x: hiker.position.x - 1,
-                    y: hiker.position.y,
-                },
-                Left => Coordinate {
-                    x: hiker.position.x,
-                    y: hiker.position.y - 1,
-                },
-                Down => Coordinate {
-                    x: hiker.position.x + 1,
-                    y: hiker.position.y,
-                },
-                Right => Coordinate {
-                    x: hiker.position.x,
-                    y: hiker.position.y + 1,
-                },
-            };
-            let tile = hike_map[next_coordinate.x][next_coordinate.y];
-            let mut forced = false;
-            match tile {
-                Rock => break,
-                LeftSlope => {
-                    if hiker.facing == Right {
-                        break;
-                    }
-                    forced = true;
-                    hiker.facing = Left
-                }
-                RightSlope => {
-                    if hiker.facing == Left {
-                        break;
-                    }
-                    forced = true;
-                    hiker.facing = Right;
-                }
-                DownSlope => {
-                    if hiker.facing == Up {
-                        break;
-                    }
-                    forced = true;
-                    hiker.facing = Down;
-                }
-                UpSlope => {
-                    if hiker.facing == Down {
-                        break;
-                    }
-                    forced = true;
-                    hiker.facing = Up;
-                }
-                Flat => {}
-            }
+// Leaving a slope is only possible downhill
+fn is_downhill(tile: Tile, facing: Facing) -> bool {
+    matches!(
+        (tile, facing),
+        (Flat, _) | (LeftSlope, Left) | (RightSlope, Right) | (UpSlope, Up) | (DownSlope, Down)
+    )
+}
 
-            if !visited_coordinates.insert(next_coordinate) {
-                break;
-            }
-            hiker.position = next_coordinate;
-            hiker.step_count += 1;
+fn opposite(facing: Facing) -> Facing {
+    match facing {
+        Up => Down,
+        Down => Up,
+        Left => Right,
+        Right => Left,
+    }
+}
 
-            if hiker.position.x == hike_map.len() - 1 {
-                max_step = max_step.max(hiker.step_count);
-                break;
-            }
+// Compress the maze into the graph of its crossroads, each corridor becoming a single weighted edge
+// With slopes, a corridor is only kept in the directions it can be walked
+fn get_junction_graph(hike_map: &[Vec<Tile>], with_slopes: bool) -> JunctionGraph {
+    let turns = [Up, Down, Left, Right];
+    let start = Coordinate {
+        x: 0,
+        y: hike_map[0]
+            .iter()
+            .position(|tile| *tile != Rock)
+            .unwrap_or(0),
+    };
+    let last_line = hike_map.len() - 1;
+    let end = Coordinate {
+        x: last_line,
+        y: hike_map[last_line]
+            .iter()
+            .position(|tile| *tile != Rock)
+            .unwrap_or(0),
+    };
 
-            if !forced {
-                let next_facing: Vec<Facing> = vec![Up, Down, Left, Right]
-                    .into_iter()
-                    .filter(|&face| match face {
-                        Up => {
-                            hiker.facing != Down
-                                && hike_map[hiker.position.x - 1][hiker.position.y] != Rock
-                                && hike_map[hiker.position.x - 1][hiker.position.y] != DownSlope
-                        }
-                        Down => {
-                            hiker.facing != Up
-                                && hike_map[hiker.position.x + 1][hiker.position.y] != Rock
-                                && hike_map[hiker.position.x + 1][hiker.position.y] != UpSlope
-                        }
-                        Left => {
-                            hiker.facing != Right
-                                && hike_map[hiker.position.x][hiker.position.y - 1] != Rock
-                                && hike_map[hiker.position.x][hiker.position.y - 1] != RightSlope
-                        }
-                        Right => {
-                            hiker.facing != Left
-                                && hike_map[hiker.position.x][hiker.position.y + 1] != Rock
-                                && hike_map[hiker.position.x][hiker.position.y + 1] != LeftSlope
-                        }
-                    })
-                    .collect();
-                hiker.facing = next_facing[0];
-                for other_facing in next_facing.into_iter().skip(1) {
-                    let mut other_hiker = hiker;
-                    other_hiker.facing = other_facing;
-                    hikers.push((other_hiker, visited_coordinates.clone()));
-                }
+    let mut junctions = vec![start, end];
+    for (x, line) in hike_map.iter().enumerate() {
+        for (y, tile) in line.iter().enumerate() {
+            let position = Coordinate { x, y };
+            if *tile != Rock
+                && turns
+                    .iter()
+                    .filter(|&&facing| step(hike_map, position, facing).is_some())
+                    .count()
+                    > 2
+            {
+                junctions.push(position);
             }
         }
     }
-    println!("🏔️ The longest, most scenic view is {} steps long", max_step);
-    Ok(())
-}
-
-pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let hike_map: Vec<Vec<Tile>> = get_input()
-        .into_iter()
-        .map(|line| {
-            line.into_iter()
-                .map(|tile| if tile == Rock { Rock } else { Flat })
-                .collect()
-        })
-        .collect();
-
-    let start_col = hike_map[0]
+    let indexes: HashMap<Coordinate, usize> = junctions
         .iter()
-        .position(|tile| *tile == Flat)
-        .unwrap_or(0);
-
-    let mut hikers = vec![(
-        Hiker {
-            position: Coordinate { x: 0, y: start_col },
-            step_count: 0,
-            facing: Down,
-        },
-        Coordinate { x: 0, y: start_col },
-    )];
+        .enumerate()
+        .map(|(index, position)| (*position, index))
+        .collect();
 
-    let mut network: HashMap<Coordinate, HashMap<Coordinate, usize>> = HashMap::new();
+    let mut edges: Vec<Vec<(usize, usize)>> = vec![vec![]; junctions.len()];
+    for (from, junction) in junctions.iter().enumerate() {
+        for &facing in turns.iter() {
+            let mut hiker = Hiker {
+                position: *junction,
+                facing,
+                step_count: 0,
+            };
+            let mut walkable = true;
 
-    let mut visited_crossroad = HashSet::new();
+            let to = loop {
+                let tile = hike_map[hiker.position.x][hiker.position.y];
+                walkable &= !with_slopes || is_downhill(tile, hiker.facing);
+                let Some(next) = step(hike_map, hiker.position, hiker.facing) else {
+                    break None;
+                };
+                hiker.position = next;
+                hiker.step_count += 1;
 
-    let turns = [Up, Down, Left, Right];
-    while let Some((mut hiker, mut last_node)) = hikers.pop() {
-        loop {
-            let mut next_pos = hiker.position;
-            match hiker.facing {
-                Up => {
-                    next_pos.x -= 1;
-                }
-                Down => {
-                    next_pos.x += 1;
-                }
-                Left => {
-                    next_pos.y -= 1;
+                if let Some(&to) = indexes.get(&next) {
+                    break Some(to);
                 }
-                Right => {
-                    next_pos.y += 1;
+                match turns.iter().find(|&&turn| {
+                    turn != opposite(hiker.facing) && step(hike_map, next, turn).is_some()
+                }) {
+                    Some(&turn) => hiker.facing = turn,
+                    None => break None,
                 }
             };
-            hiker.position = next_pos;
 
-            hiker.step_count += 1;
+            if let (Some(to), true) = (to, walkable) {
+                edges[from].push((to, hiker.step_count));
+            }
+        }
+    }
+
+    JunctionGraph {
+        junctions,
+        edges,
+        start: indexes[&start],
+        end: indexes[&end],
+    }
+}
 
-            if hiker.position.x == hike_map.len() - 1 {
-                let node = network.entry(last_node).or_default();
-                node.insert(hiker.position, hiker.step_count);
-                break;
+// Edges are labelled with their steps count, start and end of the trail are highlighted
+fn to_dot(graph: &JunctionGraph, directed: bool) -> DotGraph {
+    let mut dot = DotGraph::new("day23", directed);
+    let ids: Vec<String> = graph
+        .junctions
+        .iter()
+        .map(|junction| format!("{},{}", junction.x, junction.y))
+        .collect();
+    for (index, id) in ids.iter().enumerate() {
+        dot.node(id, None, index == graph.start || index == graph.end);
+    }
+    for (from, edges) in graph.edges.iter().enumerate() {
+        for (to, steps) in edges.iter() {
+            // Undirected corridors are listed from both of their ends
+            if directed || from <= *to {
+                dot.edge(&ids[from], &ids[*to], Some(steps.to_string()), false);
             }
+        }
+    }
+    dot
+}
 
-            let neighbors: Vec<&Facing> = turns
-                .iter()
-                .filter(|&&direction| {
-                    if hiker.facing == Up && direction == Down
-                        || hiker.facing == Down && direction == Up
-                        || hiker.facing == Left && direction == Right
-                        || hiker.facing == Right && direction == Left
-                    {
-                        false
-                    } else {
-                        let tile = match direction {
-                            Up => hike_map[hiker.position.x - 1][hiker.position.y],
-                            Down => hike_map[hiker.position.x + 1][hiker.position.y],
-                            Left => hike_map[hiker.position.x][hiker.position.y - 1],
-                            Right => hike_map[hiker.position.x][hiker.position.y + 1],
-                        };
-                        tile != Rock
-                    }
-                })
-                .collect();
+fn longest_path(graph: &JunctionGraph) -> Result<usize, Box<dyn Error + 'static>> {
+    if graph.junctions.len() > u64::BITS as usize {
+        return Err(format!("Too many crossroads to track: {}", graph.junctions.len()).into());
+    }
 
-            if neighbors.len() > 1 {
-                let node = network.entry(last_node).or_default();
-                node.insert(hiker.position, hiker.step_count);
-                let node = network.entry(hiker.position).or_default();
-                node.insert(last_node, hiker.step_count);
-                hiker.step_count = 0;
-                last_node = hiker.position;
+    // The only crossroad leading to the exit has to go straight to it, otherwise the exit is cut off
+    let to_exit: Vec<(usize, usize)> = graph
+        .edges
+        .iter()
+        .enumerate()
+        .filter_map(|(from, edges)| {
+            edges
+                .iter()
+                .find(|(to, _)| *to == graph.end)
+                .map(|(_, steps)| (from, *steps))
+        })
+        .collect();
+    let (target, last_steps) = match to_exit[..] {
+        [(before_exit, steps)] => (before_exit, steps),
+        _ => (graph.end, 0),
+    };
 
-                if !visited_crossroad.insert(hiker.position) {
-                    break;
-                }
-            }
+    longest_path_from(graph, graph.start, target, 1 << graph.start)
+        .map(|steps| steps + last_steps)
+        .ok_or_else(|| "No path leads to the exit".into())
+}
 
-            hiker.facing = *neighbors[0];
+fn longest_path_from(
+    graph: &JunctionGraph,
+    current: usize,
+    target: usize,
+    visited: u64,
+) -> Option<usize> {
+    if current == target {
+        return Some(0);
+    }
 
-            for dir in neighbors.into_iter().skip(1) {
-                let mut new_hiker = hiker;
-                new_hiker.facing = *dir;
+    graph.edges[current]
+        .iter()
+        .filter(|(next, _)| visited & (1 << next) == 0)
+        .filter_map(|&(next, steps)| {
+            longest_path_from(graph, next, target, visited | (1 << next)).map(|total| total + steps)
+        })
+        .max()
+}
 
-                hikers.push((new_hiker, last_node));
-            }
-        }
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let hike_map = get_input();
+    let graph = get_junction_graph(&hike_map, true);
+    if let Some(path) = cli::value("--dot") {
+        to_dot(&graph, true).write(&path)?;
     }
 
-    let mut to_test: Vec<(Coordinate, HashSet<Coordinate>, usize)> =
-        vec![(Coordinate { x: 0, y: start_col }, HashSet::new(), 0)];
-    let mut max_step = 0;
+    let max_step = longest_path(&graph)?;
 
-    while let Some((current, mut visited, total)) = to_test.pop() {
-        if !visited.insert(current) {
-            continue;
-        }
-        if let Some(neighbors) = network.get(&current) {
-            for (coordinate, steps) in neighbors.iter() {
-                if network.contains_key(coordinate) {
-                    to_test.push((*coordinate, visited.clone(), steps + total));
-                } else {
-                    max_step = max_step.max(total + *steps);
-                }
-            }
-        }
+    println!(
+        "🏔️ The longest, most scenic view is {} steps long",
+        max_step
+    );
+    Ok(())
+}
+
+pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
+    let hike_map = get_input();
+    let graph = get_junction_graph(&hike_map, false);
+    // --dry-dot <path> exports the undirected crossroads graph, once slopes can be climbed
+    if let Some(path) = cli::value("--dry-dot") {
+        to_dot(&graph, false).write(&path)?;
     }
 
+    let max_step = longest_path(&graph)?;
+
     println!("Since the trails are surprisingly dry, 🏔️ The longest, most scenic view is, in fact, {} steps long", max_step);
     Ok(())
 }
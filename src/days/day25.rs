@@ -1,13 +1,18 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::error::Error;
 use std::fs;
 use std::path::Path;
 
+use itertools::Itertools;
+
+use crate::cli;
+use crate::dot::DotGraph;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Edge {
-    name: String,
-    start: String,
-    end: String,
+pub struct Edge {
+    pub name: String,
+    pub start: String,
+    pub end: String,
 }
 
 impl Edge {
@@ -50,85 +55,140 @@ fn get_input() -> HashMap<String, Vec<Edge>> {
     network
 }
 
-type Network = HashMap<String, Vec<Edge>>;
-
-fn get_edge_to_remove(network: &Network) -> Edge {
-    let mut betweeness: HashMap<&String, (usize, Edge)> = HashMap::new();
-
-    for node in network.keys() {
-        let mut visited: HashSet<&String> = HashSet::new();
-        let mut nodes: VecDeque<(&String, Vec<&String>)> = VecDeque::from(vec![(node, vec![])]);
-        while let Some((node, path)) = nodes.pop_front() {
-            visited.insert(node);
-            if let Some(edges) = network.get(node) {
-                for edge in edges.iter().filter(|edge| !visited.contains(&edge.end)) {
-                    let mut next_path = path.clone();
-                    next_path.push(&edge.name);
-                    for sub_path in next_path.iter() {
-                        betweeness
-                            .entry(sub_path)
-                            .and_modify(|(x, _)| *x += 1)
-                            .or_insert((1, edge.clone()));
-                    }
-                    nodes.push_back((&edge.end, next_path));
-                }
+pub type Network = HashMap<String, Vec<Edge>>;
+
+pub struct MinCut {
+    pub edges: Vec<Edge>,
+    pub sizes: (usize, usize),
+}
+
+// Stoer-Wagner global minimum cut: https://en.wikipedia.org/wiki/Stoer%E2%80%93Wagner_algorithm
+// With a target, stops at the first cut of at most that many edges, and returns None if the minimum cut is bigger
+pub fn min_cut(network: &Network, target: Option<usize>) -> Option<MinCut> {
+    let names: Vec<&String> = network.keys().collect();
+    if names.len() < 2 {
+        return None;
+    }
+    let indexes: HashMap<&String, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (*name, index))
+        .collect();
+
+    let mut weights: Vec<HashMap<usize, usize>> = vec![HashMap::new(); names.len()];
+    for (name, edges) in network.iter() {
+        let start = indexes[name];
+        for edge in edges.iter() {
+            if let Some(&end) = indexes.get(&edge.end) {
+                *weights[start].entry(end).or_default() += 1;
             }
         }
     }
 
-    betweeness
-        .values()
-        .max_by(|(val_a, _), (val_b, _)| val_a.cmp(val_b))
-        .unwrap()
-        .1
-        .clone()
-}
+    // Each merged vertex keeps track of the original nodes it contains
+    let mut members: Vec<Vec<usize>> = (0..names.len()).map(|index| vec![index]).collect();
+    let mut active: Vec<usize> = (0..names.len()).collect();
+    let mut best: Option<(usize, Vec<usize>)> = None;
+
+    while active.len() > 1 {
+        let (cut, last, before_last) = minimum_cut_phase(&weights, &active);
 
-fn get_subsets_size(network: &HashMap<String, Vec<Edge>>) -> (usize, usize) {
-    let mut visited: HashSet<&String> = HashSet::new();
-    let start = network.keys().next().unwrap();
-    let mut to_check = vec![start];
-
-    while let Some(node) = to_check.pop() {
-        visited.insert(node);
-
-        if let Some(children) = network.get(node) {
-            to_check.extend(
-                children
-                    .iter()
-                    .filter_map(|elem| {
-                        if visited.contains(&elem.end) {
-                            None
-                        } else {
-                            Some(&elem.end)
-                        }
-                    })
-                    .collect::<Vec<&String>>(),
-            );
+        if best.as_ref().is_none_or(|(best_cut, _)| cut < *best_cut) {
+            best = Some((cut, members[last].clone()));
         }
+        if target.is_some_and(|target| cut <= target) {
+            break;
+        }
+
+        let merged = std::mem::take(&mut weights[last]);
+        for (node, weight) in merged {
+            weights[node].remove(&last);
+            if node != before_last {
+                *weights[before_last].entry(node).or_default() += weight;
+                *weights[node].entry(before_last).or_default() += weight;
+            }
+        }
+        let merged = std::mem::take(&mut members[last]);
+        members[before_last].extend(merged);
+        active.retain(|&node| node != last);
     }
-    let remaining_size = network.len() - visited.len();
-    (visited.len(), remaining_size)
+
+    let (cut, group) = best?;
+    if target.is_some_and(|target| cut > target) {
+        return None;
+    }
+
+    let group: HashSet<&String> = group.into_iter().map(|index| names[index]).collect();
+    let edges = group
+        .iter()
+        .flat_map(|name| network[*name].iter())
+        .filter(|edge| !group.contains(&edge.end))
+        .cloned()
+        .collect();
+
+    Some(MinCut {
+        edges,
+        sizes: (group.len(), names.len() - group.len()),
+    })
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let mut network = get_input();
-
-    for _ in 0..3 {
-        // How did I find it: google find which edge to remove to disconnect graph
-        // lead to https://stackoverflow.com/questions/1566967/checking-if-removing-an-edge-in-a-graph-will-result-in-the-graph-splitting
-        // lead to https://en.wikipedia.org/wiki/Girvan%E2%80%93Newman_algorithm
-        let edge_to_remove = get_edge_to_remove(&network);
-        if let Some(edges) = network.get_mut(&edge_to_remove.start) {
-            edges.retain(|edge| edge.name != edge_to_remove.name);
+// Adds the most tightly connected vertex one at a time, returns the cut weight of the last one added, along with the last two vertices
+fn minimum_cut_phase(weights: &[HashMap<usize, usize>], active: &[usize]) -> (usize, usize, usize) {
+    let mut connectivity: Vec<usize> = vec![0; weights.len()];
+    let mut added: Vec<bool> = vec![false; weights.len()];
+    let mut queue: BinaryHeap<(usize, usize)> = active.iter().map(|&node| (0, node)).collect();
+    let (mut last, mut before_last, mut cut) = (active[0], active[0], 0);
+
+    while let Some((weight, node)) = queue.pop() {
+        // Stale entries are left in the queue when a vertex connectivity increases
+        if added[node] || weight != connectivity[node] {
+            continue;
+        }
+        added[node] = true;
+        before_last = last;
+        last = node;
+        cut = weight;
+
+        for (&next, edge_weight) in weights[node].iter() {
+            if !added[next] {
+                connectivity[next] += edge_weight;
+                queue.push((connectivity[next], next));
+            }
         }
-        if let Some(edges) = network.get_mut(&edge_to_remove.end) {
-            edges.retain(|edge| edge.name != edge_to_remove.name);
+    }
+
+    (cut, last, before_last)
+}
+
+// Wires of the cut are highlighted
+fn to_dot(network: &Network, cut: &[Edge]) -> DotGraph {
+    let mut graph = DotGraph::new("day25", false);
+    let mut listed: HashSet<&String> = HashSet::new();
+    for (name, edges) in network.iter().sorted_by_key(|(name, _)| *name) {
+        graph.node(name, None, false);
+        for edge in edges.iter() {
+            if listed.insert(&edge.name) {
+                let highlighted = cut.iter().any(|cut_edge| cut_edge.name == edge.name);
+                graph.edge(&edge.start, &edge.end, None, highlighted);
+            }
         }
     }
+    graph
+}
 
-    let (size_a, size_b) = get_subsets_size(&network);
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let network = get_input();
 
+    let cut = min_cut(&network, Some(3)).ok_or("No cut of three wires found")?;
+    let (size_a, size_b) = cut.sizes;
+    if let Some(path) = cli::value("--dot") {
+        to_dot(&network, &cut.edges).write(&path)?;
+    }
+
+    println!(
+        "Wires to disconnect: {}",
+        cut.edges.iter().map(|edge| edge.name.as_str()).join(", ")
+    );
     println!(
         "The multiplication of the two subsets size is {}",
         size_a * size_b
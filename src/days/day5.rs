@@ -1,13 +1,186 @@
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
 
-use itertools::Itertools;
+use regex::Regex;
 
-type ConvertMap = [usize; 3];
+use crate::cli;
 
-fn get_input() -> (Vec<usize>, Vec<Vec<ConvertMap>>) {
-    let mut converters = vec![];
+// Piecewise translation over half-open intervals: piece i maps [start_i, start_i+1[ by adding offset_i,
+// the first piece starts at 0 and the last one goes up to i64::MAX
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeMap {
+    pieces: Vec<(i64, i64)>,
+}
+
+impl RangeMap {
+    fn identity() -> Self {
+        RangeMap {
+            pieces: vec![(0, 0)],
+        }
+    }
+
+    // Almanac lines are [destination start, source start, length], unmapped values keep their number
+    fn from_almanac(lines: &[[i64; 3]]) -> Self {
+        let mut segments: Vec<(i64, i64, i64)> = lines
+            .iter()
+            .map(|&[destination, source, length]| (source, source + length, destination - source))
+            .collect();
+        segments.sort_unstable();
+
+        let mut pieces = vec![];
+        let mut position = 0;
+        for (start, end, offset) in segments {
+            if start > position {
+                pieces.push((position, 0));
+            }
+            pieces.push((start, offset));
+            position = end;
+        }
+        pieces.push((position, 0));
+        RangeMap::from_pieces(pieces)
+    }
+
+    // Drop empty pieces and merge neighbours sharing the same offset
+    fn from_pieces(pieces: Vec<(i64, i64)>) -> Self {
+        let mut merged: Vec<(i64, i64)> = vec![];
+        for (start, offset) in pieces {
+            if merged.last().is_some_and(|last| last.0 == start) {
+                merged.pop();
+            }
+            if merged.last().is_none_or(|last| last.1 != offset) {
+                merged.push((start, offset));
+            }
+        }
+        RangeMap { pieces: merged }
+    }
+
+    fn end(&self, index: usize) -> i64 {
+        self.pieces
+            .get(index + 1)
+            .map_or(i64::MAX, |(start, _)| *start)
+    }
+
+    // Negative values are outside of the map
+    fn apply(&self, point: i64) -> Option<i64> {
+        let index = self
+            .pieces
+            .partition_point(|(start, _)| *start <= point)
+            .checked_sub(1)?;
+        Some(point + self.pieces[index].1)
+    }
+
+    // Split a range along the pieces, returns each part along with its offset, negative values are dropped
+    fn split(&self, range: &Range<i64>) -> Vec<(Range<i64>, i64)> {
+        let first = self
+            .pieces
+            .partition_point(|(start, _)| *start <= range.start)
+            .saturating_sub(1);
+        (first..self.pieces.len())
+            .take_while(|index| self.pieces[*index].0 < range.end)
+            .map(|index| {
+                let (start, offset) = self.pieces[index];
+                (
+                    start.max(range.start)..self.end(index).min(range.end),
+                    offset,
+                )
+            })
+            .collect()
+    }
+
+    fn apply_ranges(&self, ranges: &[Range<i64>]) -> Vec<Range<i64>> {
+        ranges
+            .iter()
+            .flat_map(|range| self.split(range))
+            .map(|(range, offset)| range.start + offset..range.end.saturating_add(offset))
+            .collect()
+    }
+
+    // The map applying self, then other
+    fn compose(&self, other: &RangeMap) -> RangeMap {
+        let mut pieces = vec![];
+        for (index, &(start, offset)) in self.pieces.iter().enumerate() {
+            let image = start + offset..self.end(index).saturating_add(offset);
+            for (range, other_offset) in other.split(&image) {
+                pieces.push((range.start - offset, offset + other_offset));
+            }
+        }
+        RangeMap::from_pieces(pieces)
+    }
+
+    // Only bijective maps can be inverted
+    fn invert(&self) -> Option<RangeMap> {
+        let mut images: Vec<(Range<i64>, i64)> = self
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(index, &(start, offset))| {
+                (
+                    start + offset..self.end(index).saturating_add(offset),
+                    -offset,
+                )
+            })
+            .collect();
+        images.sort_unstable_by_key(|(range, _)| range.start);
+
+        let mut position = 0;
+        for (range, _) in images.iter() {
+            if range.start != position {
+                return None;
+            }
+            position = range.end;
+        }
+        if position != i64::MAX {
+            return None;
+        }
+        Some(RangeMap::from_pieces(
+            images
+                .into_iter()
+                .map(|(range, offset)| (range.start, offset))
+                .collect(),
+        ))
+    }
+}
+
+// Maps between categories, both ways when a map can be inverted
+struct Almanac {
+    seeds: Vec<i64>,
+    maps: HashMap<String, Vec<(String, RangeMap)>>,
+}
+
+impl Almanac {
+    // Shortest chain of maps from one category to the other, collapsed into a single map
+    fn chain(&self, from: &str, to: &str) -> Result<RangeMap, Box<dyn Error + 'static>> {
+        let mut reached: HashMap<&str, RangeMap> = HashMap::from([(from, RangeMap::identity())]);
+        let mut to_visit = VecDeque::from([from]);
+
+        while let Some(category) = to_visit.pop_front() {
+            if category == to {
+                return Ok(reached.remove(to).unwrap());
+            }
+            let current = reached[category].clone();
+            for (destination, map) in self.maps.get(category).into_iter().flatten() {
+                if !reached.contains_key(destination.as_str()) {
+                    reached.insert(destination, current.compose(map));
+                    to_visit.push_back(destination);
+                }
+            }
+        }
+
+        Err(format!("No chain of maps converts {} to {}", from, to).into())
+    }
+
+    fn convert(&self, value: i64, from: &str, to: &str) -> Result<i64, Box<dyn Error + 'static>> {
+        self.chain(from, to)?
+            .apply(value)
+            .ok_or_else(|| format!("{} {} is not in the almanac", from, value).into())
+    }
+}
+
+fn get_input() -> Almanac {
+    let reg_header = Regex::new(r"(\w+)-to-(\w+) map:").unwrap();
     let input = fs::read_to_string(Path::new("./input/day5.input"))
         .expect("Something went wrong with the input");
     let mut parts = input.trim().split("\r\n\r\n");
@@ -16,16 +189,20 @@ fn get_input() -> (Vec<usize>, Vec<Vec<ConvertMap>>) {
         .unwrap()
         .split_whitespace()
         .skip(1)
-        .map(str::parse::<usize>)
+        .map(str::parse::<i64>)
         .collect::<Result<Vec<_>, _>>()
         .unwrap();
-    for maps in parts {
+    let mut maps: HashMap<String, Vec<(String, RangeMap)>> = HashMap::new();
+    for part in parts {
+        let mut lines = part.lines();
+        let header = reg_header.captures(lines.next().unwrap()).unwrap();
+        let (source, destination) = (header[1].to_string(), header[2].to_string());
         let mut mapper = vec![];
-        for line in maps.lines().skip(1) {
+        for line in lines {
             let mut range = [0; 3];
             let values = line
                 .split_whitespace()
-                .map(str::parse::<usize>)
+                .map(str::parse::<i64>)
                 .collect::<Result<Vec<_>, _>>()
                 .unwrap();
             range[0] = values[0];
@@ -33,80 +210,74 @@ fn get_input() -> (Vec<usize>, Vec<Vec<ConvertMap>>) {
             range[2] = values[2];
             mapper.push(range);
         }
-        converters.push(mapper);
+        let map = RangeMap::from_almanac(&mapper);
+        if let Some(inverse) = map.invert() {
+            maps.entry(destination.clone())
+                .or_default()
+                .push((source.clone(), inverse));
+        }
+        maps.entry(source).or_default().push((destination, map));
     }
-    (seeds, converters)
+    Almanac { seeds, maps }
 }
 
 pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let (seeds, maps) = get_input();
-    let mut locations = usize::MAX;
-
-    for seed in seeds {
-        let mut current = seed;
-        for map in &maps {
-            for item in map {
-                if item[1] <= current && item[1] + item[2] > current {
-                    current = item[0] + (current - item[1]);
-                    break;
-                }
-            }
-        }
-        locations = locations.min(current);
-    }
+    let almanac = get_input();
+    let seed_to_location = almanac.chain("seed", "location")?;
+
+    let locations = almanac
+        .seeds
+        .iter()
+        .filter_map(|seed| seed_to_location.apply(*seed))
+        .min()
+        .ok_or("No seed to plant")?;
 
     println!("The lowest seeding location is: {:?}", locations);
 
+    // --convert <value>,<from>,<to> converts between any two categories, such as 46,location,seed
+    if let Some(conversion) = cli::value("--convert") {
+        let [value, from, to] = conversion.split(',').collect::<Vec<_>>()[..] else {
+            return Err(format!("Invalid conversion: {}", conversion).into());
+        };
+        println!(
+            "{} {} is {} {}",
+            from,
+            value,
+            to,
+            almanac.convert(
+                value
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|value| i64::try_from(value).ok())
+                    .ok_or_else(|| format!("Invalid value {}, it should not be negative", value))?,
+                from,
+                to
+            )?
+        );
+    }
+
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let (seeds, maps) = get_input();
-    let mut seeds_ranges: Vec<[usize; 2]> = seeds
+    let almanac = get_input();
+    let seeds_ranges: Vec<Range<i64>> = almanac
+        .seeds
         .chunks(2)
-        .map(|seed_range| [seed_range[0], seed_range[0] + seed_range[1]])
-        .collect_vec();
-
-    for map in maps.iter() {
-        let mut next_range = vec![];
-        while !seeds_ranges.is_empty() {
-            let mut found = false;
-            let [mappable_start, mappable_end] = seeds_ranges.pop().unwrap();
-            for &[dest_start, source_start, range] in map {
-                let source_end = source_start + range;
-                // All ranges excludes outer bounds ([x, y[)
-                if source_start < mappable_end && source_end > mappable_start {
-                    let (matched_start, matched_end) = (
-                        source_start.max(mappable_start),
-                        source_end.min(mappable_end),
-                    );
-                    let unmapped_before = [mappable_start, matched_start];
-                    let unmapped_after = [matched_end, mappable_end];
-
-                    if unmapped_after[0] != unmapped_after[1] {
-                        seeds_ranges.push(unmapped_after);
-                    }
-                    if unmapped_before[0] != unmapped_before[1] {
-                        seeds_ranges.push(unmapped_before);
-                    }
-                    let mapped_start = dest_start + matched_start - source_start;
-                    let mapped_end = mapped_start + (matched_end - matched_start);
-
-                    next_range.push([mapped_start, mapped_end]);
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                next_range.push([mappable_start, mappable_end]);
-            }
-        }
-        seeds_ranges = next_range;
-    }
+        .map(|seed_range| seed_range[0]..seed_range[0] + seed_range[1])
+        .collect();
+
+    let locations = almanac
+        .chain("seed", "location")?
+        .apply_ranges(&seeds_ranges);
 
     println!(
         "The lowest seeding location, using seeding ranges, is: {}",
-        seeds_ranges.iter().map(|elem| elem[0]).min().unwrap()
+        locations
+            .iter()
+            .map(|range| range.start)
+            .min()
+            .ok_or("No seed to plant")?
     );
 
     Ok(())
@@ -1,10 +1,11 @@
 use std::error::Error;
 use std::fs;
+use std::ops::RangeInclusive;
 use std::path::Path;
 
 use itertools::Itertools;
 
-fn get_input() -> (Vec<usize>, Vec<usize>) {
+fn get_input() -> (Vec<u128>, Vec<u128>) {
     let input = fs::read_to_string(Path::new("./input/day6.input"))
         .expect("Something went wrong with the input");
     input
@@ -13,7 +14,7 @@ fn get_input() -> (Vec<usize>, Vec<usize>) {
         .map(|line| {
             line.split_whitespace()
                 .skip(1)
-                .map(str::parse::<usize>)
+                .map(str::parse::<u128>)
                 .collect::<Result<Vec<_>, _>>()
                 .unwrap()
         })
@@ -21,20 +22,61 @@ fn get_input() -> (Vec<usize>, Vec<usize>) {
         .unwrap()
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let (times, distances) = get_input();
-    let mut total = 1;
-    for (i, time) in times.iter().enumerate() {
-        let distance = distances[i];
-        let mut record_breakers = 0;
-        for preparation in 1..*time {
-            let traveled = (time - preparation) * preparation;
-            if traveled > distance {
-                record_breakers += 1;
-            }
+fn traveled(time: u128, hold: u128) -> u128 {
+    (time - hold) * hold
+}
+
+// Holding h ms beats the record when h * (time - h) > record, so h lies strictly between the roots of
+// h² - time * h + record, the interval being symmetric around time / 2
+fn winning_holds(
+    time: u128,
+    record: u128,
+) -> Result<Option<RangeInclusive<u128>>, Box<dyn Error + 'static>> {
+    let squared = time
+        .checked_mul(time)
+        .ok_or_else(|| format!("A race of {} ms is too long", time))?;
+    // Once the race fits, a record too large to quadruple is longer than any boat can go
+    let Some(discriminant) = record
+        .checked_mul(4)
+        .and_then(|quadrupled| squared.checked_sub(quadrupled))
+    else {
+        return Ok(None);
+    };
+    let mut shortest = (time - discriminant.isqrt()) / 2;
+
+    // The integer square root is exact, so the estimate is at most a step away from the shortest hold,
+    // the roots may still not be integers, or land exactly on the record
+    for _ in 0..2 {
+        if shortest <= time / 2 && traveled(time, shortest) <= record {
+            shortest += 1;
         }
-        total *= record_breakers;
     }
+    for _ in 0..2 {
+        if shortest > 0 && traveled(time, shortest - 1) > record {
+            shortest -= 1;
+        }
+    }
+
+    if shortest > time / 2 || traveled(time, shortest) <= record {
+        Ok(None)
+    } else {
+        Ok(Some(shortest..=time - shortest))
+    }
+}
+
+fn count(holds: &Option<RangeInclusive<u128>>) -> u128 {
+    holds
+        .as_ref()
+        .map_or(0, |holds| holds.end() - holds.start() + 1)
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let (times, distances) = get_input();
+    let total: u128 = times
+        .iter()
+        .zip(distances.iter())
+        .map(|(time, distance)| Ok(count(&winning_holds(*time, *distance)?)))
+        .product::<Result<u128, Box<dyn Error + 'static>>>()?;
     println!(
         "The value got by multiplying all record breakers is {}",
         total
@@ -48,23 +90,23 @@ pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
         .iter()
         .map(|elem| elem.to_string())
         .collect::<String>()
-        .parse::<usize>()
+        .parse::<u128>()
         .unwrap();
     let real_dist = distances
         .iter()
         .map(|elem| elem.to_string())
         .collect::<String>()
-        .parse::<usize>()
+        .parse::<u128>()
         .unwrap();
-    let mut total = 0;
+    let holds = winning_holds(real_time, real_dist)?;
 
-    for preparation in 1..real_time {
-        let traveled = (real_time - preparation) * preparation;
-        if traveled > real_dist {
-            total += 1;
-        }
+    println!("The amount of record breakers is {}", count(&holds));
+    if let Some(holds) = holds {
+        println!(
+            "The record is beaten by holding the button from {} to {} ms",
+            holds.start(),
+            holds.end()
+        );
     }
-
-    println!("The amount of record breakers is {}", total);
     Ok(())
 }
@@ -1,4 +1,3 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
 
 use std::error::Error;
@@ -7,14 +6,22 @@ use std::path::Path;
 
 use itertools::Itertools;
 
+use crate::cli;
+
+mod poker;
+
+// Straights and flushes only exist in poker, camel cards never produce them
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 enum Hands {
     HighCard,
     OnePair,
     TwoPair,
     ThreeOAK,
+    Straight,
+    Flush,
     FullH,
     FourOAK,
+    StraightFlush,
     FiveOAK,
 }
 
@@ -33,126 +40,151 @@ fn get_input() -> Vec<(Vec<char>, usize)> {
         .collect_vec()
 }
 
-fn get_card_score(card: &char, is_joker: bool) -> usize {
-    match card {
-        '2' => 1,
-        '3' => 2,
-        '4' => 3,
-        '5' => 4,
-        '6' => 5,
-        '7' => 6,
-        '8' => 7,
-        '9' => 8,
-        'T' => 9,
-        'J' => {
-            if is_joker {
-                0
-            } else {
-                10
-            }
+const CARDS: &str = "23456789TJQKA";
+
+// Card ordering from weakest to strongest, wild cards are the weakest and complete the largest group
+struct Ruleset {
+    ordering: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+}
+
+impl Ruleset {
+    // The ordering goes from the weakest to the strongest card, wild cards are moved in front of it
+    fn new(ordering: &str, wild: &str, hand_size: usize) -> Result<Self, Box<dyn Error + 'static>> {
+        let ordering = ordering.chars().collect_vec();
+        let wild = wild.chars().collect_vec();
+        if !ordering.iter().all_unique() {
+            return Err(format!(
+                "Cards {} are listed twice",
+                ordering.iter().collect::<String>()
+            )
+            .into());
+        }
+        if let Some(card) = wild.iter().find(|card| !ordering.contains(card)) {
+            return Err(format!("Wild card {} is not in the ordering", card).into());
+        }
+        if hand_size == 0 {
+            return Err("Hands should hold at least one card".into());
         }
-        'Q' => 11,
-        'K' => 12,
-        'A' => 13,
-        _ => unreachable!(),
+        let ordering = wild
+            .iter()
+            .cloned()
+            .chain(ordering.into_iter().filter(|card| !wild.contains(card)))
+            .collect();
+        Ok(Ruleset {
+            ordering,
+            wild,
+            hand_size,
+        })
     }
-}
 
-fn get_hand_name(hand: &[char], is_joker: bool) -> Hands {
-    let mut card_count: HashMap<&char, usize> = HashMap::new();
-    for card in hand {
-        card_count
-            .entry(card)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
+    fn standard() -> Self {
+        Ruleset::new(CARDS, "", 5).unwrap()
     }
 
-    if is_joker {
-        if let Some(joker_count) = card_count.remove(&'J') {
-            if card_count.is_empty() {
-                return Hands::FiveOAK;
-            }
-            let (max_card, _) = card_count
-                .iter()
-                .max_by(|(_, val_a), (_, val_b)| val_a.cmp(val_b))
-                .unwrap();
-            card_count
-                .entry(*max_card)
-                .and_modify(|elem| *elem += joker_count);
-        }
+    // --cards <ordering> and --hand-size <count> play another game, such as --cards 123456 --hand-size 3
+    fn from_args(wild: &str) -> Result<Self, Box<dyn Error + 'static>> {
+        let ordering = cli::value("--cards").unwrap_or_else(|| CARDS.to_string());
+        let hand_size = match cli::value("--hand-size") {
+            Some(hand_size) => hand_size.parse()?,
+            None => 5,
+        };
+        Ruleset::new(&ordering, wild, hand_size)
     }
 
-    let mut card_count = card_count.values().cloned().collect_vec();
-    card_count.sort();
-    match card_count.len() {
-        1 => Hands::FiveOAK,
-        2 => {
-            if *card_count.last().unwrap() == 4 {
-                Hands::FourOAK
+    fn card_score(&self, card: char) -> Result<usize, Box<dyn Error + 'static>> {
+        self.ordering
+            .iter()
+            .position(|known| *known == card)
+            .ok_or_else(|| format!("Unknown card {}", card).into())
+    }
+
+    // Group sizes from the largest down, wild cards joining the largest group
+    fn groups(&self, hand: &[char]) -> Vec<usize> {
+        let mut card_count: HashMap<&char, usize> = HashMap::new();
+        let mut wild_count = 0;
+        for card in hand {
+            if self.wild.contains(card) {
+                wild_count += 1;
             } else {
-                Hands::FullH
+                *card_count.entry(card).or_insert(0) += 1;
             }
         }
-        3 => {
-            if *card_count.last().unwrap() == 3 {
-                Hands::ThreeOAK
-            } else {
-                Hands::TwoPair
-            }
+
+        let mut groups = card_count.values().cloned().collect_vec();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        match groups.first_mut() {
+            Some(largest) => *largest += wild_count,
+            None => groups.push(wild_count),
         }
-        4 => Hands::OnePair,
-        _ => Hands::HighCard,
+        groups
     }
-}
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let mut input = get_input();
-    input.sort_by(|(left_hand, _), (right_hand, _)| {
-        let left_hand_name = get_hand_name(left_hand, false);
-        let right_hand_name = get_hand_name(right_hand, false);
-
-        if left_hand_name != right_hand_name {
-            left_hand_name.cmp(&right_hand_name)
-        } else {
-            for i in 0..5 {
-                if left_hand[i] != right_hand[i] {
-                    return get_card_score(&left_hand[i], false)
-                        .cmp(&get_card_score(&right_hand[i], false));
-                }
-            }
-            Ordering::Equal
+    // Only 5-card hands have a name
+    fn hand_name(&self, hand: &[char]) -> Hands {
+        match self.groups(hand)[..] {
+            [5] => Hands::FiveOAK,
+            [4, 1] => Hands::FourOAK,
+            [3, 2] => Hands::FullH,
+            [3, 1, 1] => Hands::ThreeOAK,
+            [2, 2, 1] => Hands::TwoPair,
+            [2, 1, 1, 1] => Hands::OnePair,
+            _ => Hands::HighCard,
         }
-    });
-    let mut score = 0;
-    for (rank, (_, bet)) in input.into_iter().enumerate() {
-        score += (rank + 1) * bet;
     }
+
+    // Hands compare by their groups first, which for 5 cards is the order of their names, then card by card
+    fn sort_key(
+        &self,
+        hand: &[char],
+    ) -> Result<(Vec<usize>, Vec<usize>), Box<dyn Error + 'static>> {
+        if hand.len() != self.hand_size {
+            return Err(format!(
+                "Hand {} should hold {} cards",
+                hand.iter().collect::<String>(),
+                self.hand_size
+            )
+            .into());
+        }
+        let scores = hand
+            .iter()
+            .map(|card| self.card_score(*card))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((self.groups(hand), scores))
+    }
+
+    fn winnings(&self, hands: &[(Vec<char>, usize)]) -> Result<usize, Box<dyn Error + 'static>> {
+        let mut keyed = hands
+            .iter()
+            .map(|(hand, bet)| Ok((self.sort_key(hand)?, *bet)))
+            .collect::<Result<Vec<_>, Box<dyn Error + 'static>>>()?;
+        keyed.sort_unstable();
+        Ok(keyed
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (_, bet))| (rank + 1) * bet)
+            .sum())
+    }
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let input = get_input();
+    let score = Ruleset::from_args("")?.winnings(&input)?;
     println!("Total winnings are: {:?}", score);
+
+    // --poker <hand>,<hand>... ranks standard poker hands, such as "AS KD 3H 3C 9S,2H 2D 2C KS KH"
+    if let Some(hands) = cli::value("--poker") {
+        poker::rank(&hands)?;
+    }
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let mut input = get_input();
-    input.sort_by(|(left_hand, _), (right_hand, _)| {
-        let left_hand_name = get_hand_name(left_hand, true);
-        let right_hand_name = get_hand_name(right_hand, true);
-
-        if left_hand_name != right_hand_name {
-            left_hand_name.cmp(&right_hand_name)
-        } else {
-            for i in 0..5 {
-                if left_hand[i] != right_hand[i] {
-                    return get_card_score(&left_hand[i], true)
-                        .cmp(&get_card_score(&right_hand[i], true));
-                }
-            }
-            Ordering::Equal
-        }
-    });
-    let mut score = 0;
-    for (rank, (_, bet)) in input.into_iter().enumerate() {
-        score += (rank + 1) * bet;
-    }
+    let input = get_input();
+    // --wild <cards> picks other wild cards than the jokers, such as J2
+    let wild = cli::value("--wild").unwrap_or_else(|| "J".to_string());
+    let score = Ruleset::from_args(&wild)?.winnings(&input)?;
     println!("Total winnings (jokers included) are: {:?}", score);
     Ok(())
 }
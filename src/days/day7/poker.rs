@@ -0,0 +1,145 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use itertools::Itertools;
+
+use super::{Hands, Ruleset};
+
+const SUITS: &str = "SHDC";
+
+// A standard 5-card poker hand, ranked by its name then its kickers, suits never break ties
+#[derive(Debug)]
+struct PokerHand {
+    cards: Vec<(char, char)>,
+    name: Hands,
+    kickers: Vec<usize>,
+}
+
+impl PokerHand {
+    fn parse(hand: &str) -> Result<Self, Box<dyn Error + 'static>> {
+        let ruleset = Ruleset::standard();
+        let cards = hand
+            .split_whitespace()
+            .map(|card| match card.chars().collect_vec()[..] {
+                [rank, suit] if SUITS.contains(suit) => Ok((rank, suit)),
+                _ => Err(format!("Invalid card {}", card)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if cards.len() != ruleset.hand_size || !cards.iter().all_unique() {
+            return Err(format!("{} is not a poker hand", hand).into());
+        }
+
+        let ranks = cards
+            .iter()
+            .map(|(rank, _)| ruleset.card_score(*rank))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Kickers go from the largest group down, then from the highest rank down
+        let kickers = ranks
+            .iter()
+            .counts()
+            .into_iter()
+            .sorted_by(|(rank_a, count_a), (rank_b, count_b)| {
+                count_b.cmp(count_a).then(rank_b.cmp(rank_a))
+            })
+            .map(|(rank, _)| *rank)
+            .collect_vec();
+
+        let flush = cards.iter().map(|(_, suit)| suit).all_equal();
+        // The ace also plays low in the 5-4-3-2-A straight, topped by the 5
+        let straight = match kickers[..] {
+            [high, .., low] if kickers.len() == 5 && high - low == 4 => Some(high),
+            [12, 3, 2, 1, 0] => Some(3),
+            _ => None,
+        };
+
+        let (name, kickers) = match (straight, flush) {
+            (Some(high), true) => (Hands::StraightFlush, vec![high]),
+            (Some(high), false) => (Hands::Straight, vec![high]),
+            (None, true) => (Hands::Flush, kickers),
+            (None, false) => (ruleset.hand_name(&Self::ranks(&cards)), kickers),
+        };
+        Ok(PokerHand {
+            cards,
+            name,
+            kickers,
+        })
+    }
+
+    fn ranks(cards: &[(char, char)]) -> Vec<char> {
+        cards.iter().map(|(rank, _)| *rank).collect()
+    }
+
+    // The same cards played as camel cards, without wild cards
+    fn camel_cmp(&self, other: &Self) -> Ordering {
+        let ruleset = Ruleset::standard();
+        let key = |hand: &Self| ruleset.sort_key(&Self::ranks(&hand.cards)).unwrap();
+        key(self).cmp(&key(other))
+    }
+}
+
+// Hands of the same strength are equal, whatever their cards, to agree with Ord
+impl PartialEq for PokerHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PokerHand {}
+
+impl Ord for PokerHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.name, &self.kickers).cmp(&(&other.name, &other.kickers))
+    }
+}
+
+impl PartialOrd for PokerHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for PokerHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cards = self
+            .cards
+            .iter()
+            .map(|(rank, suit)| format!("{}{}", rank, suit))
+            .join(" ");
+        let kickers = self
+            .kickers
+            .iter()
+            .map(|rank| super::CARDS.as_bytes()[*rank] as char)
+            .join("");
+        write!(f, "{} ({:?}, kickers {})", cards, self.name, kickers)
+    }
+}
+
+// Print the hands from the strongest down, along with their rank as camel cards
+pub fn rank(hands: &str) -> Result<(), Box<dyn Error + 'static>> {
+    let hands = hands
+        .split(',')
+        .map(PokerHand::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    let camel_ranks = (0..hands.len())
+        .sorted_by(|a, b| hands[*b].camel_cmp(&hands[*a]))
+        .collect_vec();
+
+    for (poker_rank, index) in (0..hands.len())
+        .sorted_by(|a, b| hands[*b].cmp(&hands[*a]))
+        .enumerate()
+    {
+        let camel_rank = camel_ranks
+            .iter()
+            .position(|other| *other == index)
+            .unwrap();
+        println!(
+            "#{} {}, #{} as camel cards",
+            poker_rank + 1,
+            hands[index],
+            camel_rank + 1
+        );
+    }
+    Ok(())
+}
@@ -6,6 +6,9 @@ use std::path::Path;
 use itertools::Itertools;
 use regex::Regex;
 
+use crate::cli;
+use crate::dot::DotGraph;
+
 type Network = HashMap<String, (String, String)>;
 
 fn get_input() -> (Vec<char>, Network) {
@@ -27,8 +30,22 @@ fn get_input() -> (Vec<char>, Network) {
     (movement, network)
 }
 
+// Starting and ending nodes of both stars are highlighted
+fn to_dot(network: &Network) -> DotGraph {
+    let mut graph = DotGraph::new("day8", true);
+    for (node, (left, right)) in network.iter().sorted() {
+        graph.node(node, None, node.ends_with('A') || node.ends_with('Z'));
+        graph.edge(node, left, Some(String::from("L")), false);
+        graph.edge(node, right, Some(String::from("R")), false);
+    }
+    graph
+}
+
 pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
     let (movements, network) = get_input();
+    if let Some(path) = cli::value("--dot") {
+        to_dot(&network).write(&path)?;
+    }
     let mut steps = 0;
     let mut current_node = "AAA";
     let end_node = "ZZZ";
@@ -4,6 +4,9 @@ use std::path::Path;
 
 use itertools::Itertools;
 
+use crate::bigint::BigInt;
+use crate::cli;
+
 fn get_input() -> Vec<Vec<isize>> {
     let input = fs::read_to_string(Path::new("./input/day9.input"))
         .expect("Something went wrong with the input");
@@ -19,50 +22,98 @@ fn get_input() -> Vec<Vec<isize>> {
         .collect_vec()
 }
 
-fn predict(input: &[isize], previous: bool) -> isize {
-    if input.iter().all(|&value| value == 0) {
-        0
-    } else {
-        let mut sub_input = vec![];
-        for i in 1..input.len() {
-            sub_input.push(input[i] - input[i - 1]);
+// Minimal-degree polynomial through the readings, taken at indices 0, 1, 2...
+// Stored in Newton form: p(x) = sum of differences[k] * C(x, k), with the differences of order k at index 0
+struct Polynomial {
+    differences: Vec<i128>,
+}
+
+impl Polynomial {
+    fn fit(readings: &[isize]) -> Self {
+        let mut row = readings.iter().map(|&value| value as i128).collect_vec();
+        let mut differences = vec![];
+        while row.iter().any(|&value| value != 0) {
+            differences.push(row[0]);
+            for i in 1..row.len() {
+                row[i - 1] = row[i] - row[i - 1];
+            }
+            row.pop();
         }
-        let prediction = predict(&sub_input, previous);
-        if previous {
-            input[0] - prediction
-        } else {
-            input.last().unwrap() + prediction
+        Polynomial { differences }
+    }
+
+    // The zero polynomial is given degree 0
+    fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    // C(x, k + 1) = C(x, k) * (x - k) / (k + 1), the division is always exact, even for negative x
+    fn evaluate(&self, index: i128) -> BigInt {
+        let mut binomial = BigInt::from(1);
+        let mut value = BigInt::from(0);
+        for (k, difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                binomial = (&binomial * &BigInt::from(index - k as i128 + 1)).div_exact(k as u32);
+            }
+            value = &value + &(&BigInt::from(*difference) * &binomial);
         }
+        value
     }
 }
 
-pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
-    let readings = get_input();
-
-    let predicted = readings
+fn sum_at<F: Fn(&[isize]) -> i128>(polynomials: &[(Vec<isize>, Polynomial)], index: F) -> BigInt {
+    polynomials
         .iter()
-        .map(|input| predict(input, false))
-        .collect_vec();
+        .fold(BigInt::from(0), |total, (readings, polynomial)| {
+            &total + &polynomial.evaluate(index(readings))
+        })
+}
+
+fn get_polynomials() -> Vec<(Vec<isize>, Polynomial)> {
+    get_input()
+        .into_iter()
+        .map(|readings| {
+            let polynomial = Polynomial::fit(&readings);
+            (readings, polynomial)
+        })
+        .collect_vec()
+}
+
+pub fn first_star() -> Result<(), Box<dyn Error + 'static>> {
+    let polynomials = get_polynomials();
 
     println!(
         "The sum of all predicted inputs in the history is: {}",
-        predicted.iter().sum::<isize>()
+        sum_at(&polynomials, |readings| readings.len() as i128)
     );
 
+    // --at <index> extrapolates every history to any index, such as 1000000000000
+    if let Some(index) = cli::value("--at") {
+        let index = index.parse::<i128>()?;
+        println!(
+            "The sum of all inputs at index {} is: {}",
+            index,
+            sum_at(&polynomials, |_| index)
+        );
+        println!(
+            "The histories have a degree up to {}",
+            polynomials
+                .iter()
+                .map(|(_, polynomial)| polynomial.degree())
+                .max()
+                .unwrap_or(0)
+        );
+    }
+
     Ok(())
 }
 
 pub fn second_star() -> Result<(), Box<dyn Error + 'static>> {
-    let readings = get_input();
-
-    let predicted = readings
-        .iter()
-        .map(|input| predict(input, true))
-        .collect_vec();
+    let polynomials = get_polynomials();
 
     println!(
         "The sum of all previous inputs in the history is: {}",
-        predicted.iter().sum::<isize>()
+        sum_at(&polynomials, |_| -1)
     );
 
     Ok(())
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+struct Node {
+    id: String,
+    label: Option<String>,
+    highlighted: bool,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    highlighted: bool,
+}
+
+// GraphViz export, render with `dot -Tsvg graph.dot -o graph.svg`
+pub struct DotGraph {
+    name: String,
+    directed: bool,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl DotGraph {
+    pub fn new(name: &str, directed: bool) -> Self {
+        DotGraph {
+            name: name.to_string(),
+            directed,
+            nodes: vec![],
+            edges: vec![],
+        }
+    }
+
+    pub fn node(&mut self, id: &str, label: Option<String>, highlighted: bool) {
+        self.nodes.push(Node {
+            id: id.to_string(),
+            label,
+            highlighted,
+        });
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str, label: Option<String>, highlighted: bool) {
+        self.edges.push(Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label,
+            highlighted,
+        });
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        fs::write(Path::new(path), self.to_string())
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn attributes(label: &Option<String>, highlighted: bool) -> String {
+    let mut attributes = vec![];
+    if let Some(label) = label {
+        attributes.push(format!("label={}", quote(label)));
+    }
+    if highlighted {
+        attributes.push(String::from("color=red, penwidth=3"));
+    }
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+impl fmt::Display for DotGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(f, "{} {} {{", kind, quote(&self.name))?;
+        for node in self.nodes.iter() {
+            writeln!(
+                f,
+                "    {}{};",
+                quote(&node.id),
+                attributes(&node.label, node.highlighted)
+            )?;
+        }
+        for edge in self.edges.iter() {
+            writeln!(
+                f,
+                "    {} {} {}{};",
+                quote(&edge.from),
+                arrow,
+                quote(&edge.to),
+                attributes(&edge.label, edge.highlighted)
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
@@ -4,7 +4,11 @@ use std::time::Instant;
 
 type Star = fn() -> Result<(), Box<dyn Error + 'static>>;
 
+mod bigint;
+mod cli;
 mod days;
+mod dot;
+mod polygon;
 
 fn main() {
     let mut buffer = String::new();
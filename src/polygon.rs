@@ -0,0 +1,63 @@
+// Lattice polygon measures: https://en.wikipedia.org/wiki/Shoelace_formula and https://en.wikipedia.org/wiki/Pick%27s_theorem
+pub struct Polygon {
+    vertices: Vec<(i64, i64)>,
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Polygon {
+    // The polygon is implicitly closed, the last vertex connects back to the first one
+    pub fn from_vertices(vertices: Vec<(i64, i64)>) -> Self {
+        Polygon { vertices }
+    }
+
+    // Each instruction is a unit direction and a number of steps, starting from the origin
+    pub fn from_instructions(instructions: impl IntoIterator<Item = ((i64, i64), i64)>) -> Self {
+        let mut position = (0, 0);
+        let mut vertices = vec![position];
+        for ((dx, dy), steps) in instructions {
+            position = (position.0 + dx * steps, position.1 + dy * steps);
+            vertices.push(position);
+        }
+        // Closing the loop brings back the origin, no need to count it twice
+        if vertices.len() > 1 && vertices.last() == vertices.first() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&(i64, i64), &(i64, i64))> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    // Twice the shoelace area, so it always stays an integer
+    pub fn double_area(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum::<i64>()
+            .abs()
+    }
+
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| gcd((b.0 - a.0).abs(), (b.1 - a.1).abs()))
+            .sum()
+    }
+
+    // Pick's theorem: A = I + B/2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+}